@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Recursively collects every file below `root`
+///
+/// ### Returns
+///
+/// * `Vec<(String, PathBuf)>` - Pairs of `/`-separated paths relative to `root` and the file on disk
+pub fn collect_files(root: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files = Vec::new();
+    if root.is_dir() {
+        collect_files_into(root, "", &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files_into(
+    dir: &Path,
+    prefix: &str,
+    files: &mut Vec<(String, PathBuf)>,
+) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_files_into(&path, &relative, files)?;
+        } else {
+            files.push((relative, path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the given files into a zip archive at `dest`
+///
+/// ### Arguments
+///
+/// * `entries` - Pairs of archive paths and their contents
+/// * `dest` - Path of the archive to create
+pub fn write_zip(entries: &[(String, Vec<u8>)], dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let file =
+        File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    zip_into(entries, file)
+}
+
+/// Builds a zip archive of the given files in memory
+pub fn zip_bytes(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    zip_into(entries, &mut buffer)?;
+    Ok(buffer.into_inner())
+}
+
+fn zip_into<W: Write + Seek>(entries: &[(String, Vec<u8>)], writer: W) -> Result<(), String> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (name, contents) in entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {} to archive: {}", name, e))?;
+        zip.write_all(contents)
+            .map_err(|e| format!("Failed to write {} to archive: {}", name, e))?;
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+    Ok(())
+}
+
+/// Reads every file of a zip archive into memory, keyed by its archive path
+pub fn read_zip(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    unzip(file).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Reads every file of an in-memory zip archive, keyed by its archive path
+pub fn read_zip_bytes(bytes: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    unzip(Cursor::new(bytes)).map_err(|e| format!("Failed to read archive: {}", e))
+}
+
+fn unzip<R: Read + Seek>(reader: R) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let mut zip = ZipArchive::new(reader).map_err(|e| e.to_string())?;
+
+    let mut files = BTreeMap::new();
+    for index in 0..zip.len() {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        // Reject entries that would escape the extraction directory
+        let name = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Invalid path in archive: {}", entry.name()))?
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .map_err(|e| format!("Failed to read {} from archive: {}", name, e))?;
+        files.insert(name, contents);
+    }
+
+    Ok(files)
+}
+
+/// Replaces the contents of `dir` with the given files
+///
+/// Hidden entries such as a pattern's `.history` are kept.
+///
+/// ### Arguments
+///
+/// * `dir` - Directory to (re)create
+/// * `files` - `/`-separated paths relative to `dir` and their contents
+pub fn write_dir(dir: &Path, files: &BTreeMap<String, Vec<u8>>) -> Result<(), String> {
+    if dir.exists() {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            removed.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+    }
+
+    for (file, contents) in files {
+        let path = dir.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_dir_replaces_files_but_keeps_hidden_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("summarize");
+        fs::create_dir_all(dir.join(".history").join("1")).unwrap();
+        fs::write(dir.join(".history").join("1").join("system.md"), "old").unwrap();
+        fs::write(dir.join("system.md"), "old").unwrap();
+        fs::write(dir.join("user.md"), "old").unwrap();
+
+        let files = BTreeMap::from([("system.md".to_string(), b"new".to_vec())]);
+        write_dir(&dir, &files).unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("system.md")).unwrap(), "new");
+        assert!(!dir.join("user.md").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join(".history").join("1").join("system.md")).unwrap(),
+            "old"
+        );
+    }
+}
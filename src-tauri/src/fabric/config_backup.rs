@@ -0,0 +1,533 @@
+use crate::fabric::archive::{collect_files, read_zip_bytes, zip_bytes};
+use crate::fabric::dotenv::{write_private_file, EnvFile};
+use crate::fabric::paths::{get_custom_patterns_dir, get_fabric_config_dir};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+/// Marks a file as an encrypted fabric backup, followed by the salt, nonce and ciphertext
+const MAGIC: &[u8] = b"FABRICBAK1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+/// Paths in the config directory that are rebuilt or only make sense locally
+const EXCLUDED_DIRS: &[&str] = &["snapshots/"];
+
+/// Which directory a backed up file belongs to
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupRoot {
+    /// The fabric config directory: .env, contexts, sessions, patterns
+    Config,
+    /// The custom patterns directory, when it lives outside the config directory
+    CustomPatterns,
+}
+
+impl BackupRoot {
+    fn prefix(self) -> &'static str {
+        match self {
+            BackupRoot::Config => "config/",
+            BackupRoot::CustomPatterns => "custom_patterns/",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BackupFile {
+    root: BackupRoot,
+    path: String,
+    size: u64,
+    /// Last modification as unix seconds
+    modified: u64,
+    checksum: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackupManifest {
+    version: u32,
+    created_at: u64,
+    files: Vec<BackupFile>,
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreAction {
+    /// The file doesn't exist locally
+    Create,
+    /// The local file is older than the backed up one
+    Overwrite,
+    /// The local file is newer and is only replaced when asked
+    SkipNewer,
+    /// The local file already has the same content
+    Unchanged,
+}
+
+#[derive(Serialize)]
+pub struct RestoreItem {
+    root: BackupRoot,
+    path: String,
+    action: RestoreAction,
+}
+
+#[derive(Serialize)]
+pub struct RestorePreview {
+    created_at: u64,
+    items: Vec<RestoreItem>,
+}
+
+fn checksum(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Whether a config file is left out of backups
+fn is_excluded(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    EXCLUDED_DIRS.iter().any(|dir| path.starts_with(dir))
+        || file_name.ends_with(".lock")
+        || (file_name.starts_with('.') && file_name.ends_with(".tmp"))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive backup key: {}", e))?;
+    Ok(Key::from(key))
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt backup".to_string())?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| "Not a fabric backup".to_string())?;
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        return Err("Backup is truncated".to_string());
+    }
+
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong passphrase or corrupted backup".to_string())
+}
+
+/// Gets the local directory of each backup root
+async fn backup_roots(app: AppHandle) -> Result<Vec<(BackupRoot, PathBuf)>, String> {
+    Ok(vec![
+        (
+            BackupRoot::Config,
+            get_fabric_config_dir(app.clone()).await?,
+        ),
+        (
+            BackupRoot::CustomPatterns,
+            get_custom_patterns_dir(app).await?,
+        ),
+    ])
+}
+
+/// Decrypts a backup and checks its files against the manifest
+fn open_backup(
+    archive: &Path,
+    passphrase: &str,
+) -> Result<(BackupManifest, BTreeMap<String, Vec<u8>>), String> {
+    let data = fs::read(archive).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let mut files = read_zip_bytes(&decrypt(&data, passphrase)?)?;
+
+    let manifest: BackupManifest = files
+        .remove(MANIFEST_FILE)
+        .ok_or_else(|| "Backup has no manifest".to_string())
+        .and_then(|json| {
+            serde_json::from_slice(&json).map_err(|e| format!("Invalid backup manifest: {}", e))
+        })?;
+
+    for file in &manifest.files {
+        // Only plain relative paths, so nothing can be written outside its root
+        if Path::new(&file.path)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return Err(format!("Invalid path in backup: {}", file.path));
+        }
+
+        let name = format!("{}{}", file.root.prefix(), file.path);
+        let contents = files
+            .get(&name)
+            .ok_or_else(|| format!("Backup is missing {}", name))?;
+        if checksum(contents) != file.checksum {
+            return Err(format!("Checksum mismatch for {}", name));
+        }
+    }
+
+    Ok((manifest, files))
+}
+
+/// Works out what restoring each file of a backup would do
+fn plan_restore(
+    manifest: &BackupManifest,
+    roots: &[(BackupRoot, PathBuf)],
+) -> Vec<(RestoreItem, PathBuf)> {
+    manifest
+        .files
+        .iter()
+        .filter_map(|file| {
+            let (_, root_dir) = roots.iter().find(|(root, _)| *root == file.root)?;
+            let path = root_dir.join(&file.path);
+
+            let action = match fs::read(&path) {
+                Err(_) => RestoreAction::Create,
+                Ok(local) if checksum(&local) == file.checksum => RestoreAction::Unchanged,
+                Ok(_) if modified_secs(&path) > file.modified => RestoreAction::SkipNewer,
+                Ok(_) => RestoreAction::Overwrite,
+            };
+
+            Some((
+                RestoreItem {
+                    root: file.root,
+                    path: file.path.clone(),
+                    action,
+                },
+                path,
+            ))
+        })
+        .collect()
+}
+
+/// Writes a file from a backup in place, readable only by the user
+fn write_restored_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    // Every .env, including those of profiles, is replaced under its lock so
+    // the restore can't interleave with another edit
+    if path.file_name().is_some_and(|name| name == ".env") {
+        return EnvFile::update(path, |env| {
+            *env = EnvFile::parse(&String::from_utf8_lossy(contents));
+            Ok(())
+        });
+    }
+
+    write_private_file(path, contents)
+}
+
+/// Writes an encrypted backup of the fabric configuration
+///
+/// The archive holds the config directory (.env, contexts, sessions and patterns)
+/// and the custom patterns directory, together with a manifest of every file.
+/// API keys kept in the OS keyring are not part of the backup.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `dest` - Path of the backup file to write
+/// * `passphrase` - Passphrase the backup is encrypted with
+///
+/// ### Returns
+///
+/// * `Result<BackupManifest, String>` - The manifest of the backup or error if operation fails
+#[tauri::command]
+pub async fn backup_config(
+    app: AppHandle,
+    dest: PathBuf,
+    passphrase: String,
+) -> Result<BackupManifest, String> {
+    if passphrase.is_empty() {
+        return Err("A passphrase is required".to_string());
+    }
+
+    let mut entries = Vec::new();
+    let mut files = Vec::new();
+
+    let roots = backup_roots(app).await?;
+    let config_dir = roots[0].1.clone();
+
+    for (root, dir) in roots {
+        // A custom patterns directory inside the config directory is backed up with it
+        if root == BackupRoot::CustomPatterns && dir.starts_with(&config_dir) {
+            continue;
+        }
+
+        for (relative, path) in collect_files(&dir)? {
+            if root == BackupRoot::Config && is_excluded(&relative) {
+                continue;
+            }
+
+            let contents =
+                fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            files.push(BackupFile {
+                root,
+                path: relative.clone(),
+                size: contents.len() as u64,
+                modified: modified_secs(&path),
+                checksum: checksum(&contents),
+            });
+            entries.push((format!("{}{}", root.prefix(), relative), contents));
+        }
+    }
+
+    let manifest = BackupManifest {
+        version: MANIFEST_VERSION,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        files,
+    };
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    entries.insert(0, (MANIFEST_FILE.to_string(), manifest_json));
+
+    let data = encrypt(&zip_bytes(&entries)?, &passphrase)?;
+    write_private_file(&dest, &data)?;
+
+    Ok(manifest)
+}
+
+/// Shows what restoring a backup would change without writing anything
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `archive` - Path of the backup file
+/// * `passphrase` - Passphrase the backup was encrypted with
+///
+/// ### Returns
+///
+/// * `Result<RestorePreview, String>` - What would happen to each file
+#[tauri::command]
+pub async fn preview_config_restore(
+    app: AppHandle,
+    archive: PathBuf,
+    passphrase: String,
+) -> Result<RestorePreview, String> {
+    let (manifest, _) = open_backup(&archive, &passphrase)?;
+    let roots = backup_roots(app).await?;
+
+    Ok(RestorePreview {
+        created_at: manifest.created_at,
+        items: plan_restore(&manifest, &roots)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect(),
+    })
+}
+
+/// Restores a backup of the fabric configuration
+///
+/// Files that changed locally since the backup was made are kept unless
+/// `overwrite_newer` is set.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `archive` - Path of the backup file
+/// * `passphrase` - Passphrase the backup was encrypted with
+/// * `overwrite_newer` - Whether to also replace files that are newer than the backup
+///
+/// ### Returns
+///
+/// * `Result<Vec<RestoreItem>, String>` - What was done with each file
+#[tauri::command]
+pub async fn restore_config(
+    app: AppHandle,
+    archive: PathBuf,
+    passphrase: String,
+    overwrite_newer: Option<bool>,
+) -> Result<Vec<RestoreItem>, String> {
+    let (manifest, files) = open_backup(&archive, &passphrase)?;
+    let roots = backup_roots(app).await?;
+    let overwrite_newer = overwrite_newer.unwrap_or(false);
+
+    let mut items = Vec::new();
+    for (mut item, path) in plan_restore(&manifest, &roots) {
+        if item.action == RestoreAction::SkipNewer && overwrite_newer {
+            item.action = RestoreAction::Overwrite;
+        }
+
+        if matches!(
+            item.action,
+            RestoreAction::Create | RestoreAction::Overwrite
+        ) {
+            let contents = &files[&format!("{}{}", item.root.prefix(), item.path)];
+            write_restored_file(&path, contents)?;
+        }
+
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes an encrypted backup holding `entries` under the config root, with the manifest listing `listed`
+    fn write_backup(
+        dir: &Path,
+        entries: &[(&str, &str)],
+        listed: Vec<BackupFile>,
+        passphrase: &str,
+    ) -> PathBuf {
+        let manifest = BackupManifest {
+            version: MANIFEST_VERSION,
+            created_at: 0,
+            files: listed,
+        };
+        let mut zip_entries = vec![(
+            MANIFEST_FILE.to_string(),
+            serde_json::to_vec(&manifest).unwrap(),
+        )];
+        for (path, contents) in entries {
+            zip_entries.push((
+                format!("{}{}", BackupRoot::Config.prefix(), path),
+                contents.as_bytes().to_vec(),
+            ));
+        }
+
+        let path = dir.join("backup.fabricbak");
+        let data = encrypt(&zip_bytes(&zip_entries).unwrap(), passphrase).unwrap();
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn listed(path: &str, contents: &str) -> BackupFile {
+        BackupFile {
+            root: BackupRoot::Config,
+            path: path.to_string(),
+            size: contents.len() as u64,
+            modified: 0,
+            checksum: checksum(contents.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn encrypted_data_round_trips() {
+        let data = encrypt(b"OPENAI_API_KEY=sk-secret\n", "correct horse").unwrap();
+
+        assert!(data.starts_with(MAGIC));
+        assert!(!data
+            .windows(b"sk-secret".len())
+            .any(|window| window == b"sk-secret"));
+        assert_eq!(
+            decrypt(&data, "correct horse").unwrap(),
+            b"OPENAI_API_KEY=sk-secret\n"
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_or_foreign_data_is_rejected() {
+        let data = encrypt(b"contents", "correct horse").unwrap();
+
+        assert!(decrypt(&data, "battery staple").is_err());
+        assert!(decrypt(&data[..MAGIC.len() + SALT_LEN], "correct horse").is_err());
+        assert!(decrypt(b"PK\x03\x04", "correct horse").is_err());
+    }
+
+    #[test]
+    fn backup_with_matching_checksums_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = write_backup(
+            dir.path(),
+            &[(".env", "DEFAULT_MODEL=gpt-4o\n")],
+            vec![listed(".env", "DEFAULT_MODEL=gpt-4o\n")],
+            "pass",
+        );
+
+        let (manifest, files) = open_backup(&archive, "pass").unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert_eq!(files["config/.env"], b"DEFAULT_MODEL=gpt-4o\n");
+        assert!(open_backup(&archive, "wrong").is_err());
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = write_backup(
+            dir.path(),
+            &[(".env", "DEFAULT_MODEL=tampered\n")],
+            vec![listed(".env", "DEFAULT_MODEL=gpt-4o\n")],
+            "pass",
+        );
+
+        let err = open_backup(&archive, "pass").err().unwrap();
+        assert!(err.contains("Checksum mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn paths_leaving_their_root_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in [
+            "../.bashrc",
+            "contexts/../../.bashrc",
+            "/etc/passwd",
+            "./.env",
+        ] {
+            let archive = write_backup(
+                dir.path(),
+                &[(path, "evil")],
+                vec![listed(path, "evil")],
+                "pass",
+            );
+
+            let err = open_backup(&archive, "pass").err().unwrap();
+            assert!(err.contains("Invalid path"), "{}: {}", path, err);
+        }
+    }
+
+    #[test]
+    fn profile_env_files_are_restored_like_the_default_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join("profiles").join("work").join(".env");
+
+        write_restored_file(&env_path, b"DEFAULT_MODEL=gpt-4o\n").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "DEFAULT_MODEL=gpt-4o\n"
+        );
+        // Restored through EnvFile::update, which keeps a lock file next to it
+        assert!(dir
+            .path()
+            .join("profiles")
+            .join("work")
+            .join(".env.lock")
+            .exists());
+    }
+}
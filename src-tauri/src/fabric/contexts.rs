@@ -1,15 +1,72 @@
-use crate::fabric::patterns::run_fabric;
-use crate::fabric::secrets::update_secret;
-use std::path::PathBuf;
+use crate::fabric::dotenv::EnvFile;
+use crate::fabric::markdown::split_front_matter;
+use crate::fabric::names::{safe_join, validate_name};
+use crate::fabric::profiles::{active_overrides, profile_env};
+use crate::fabric::run::{run_fabric_with_input, DEFAULT_FABRIC_BIN};
+use crate::fabric::secret_store::api_key_env;
+use crate::fabric::secrets::{get_env_file_path, load_secret, update_secret};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 use tauri::{AppHandle, Error, Manager};
 
+/// .env key holding the context fabric adds to every run
+const CURRENT_CONTEXT_KEY: &str = "CURRENT_CONTEXT";
+
+/// Runs fabric with a context, using the active profile's settings
+///
+/// fabric only reads contexts from its own directory, so this fails for a
+/// profile that keeps its contexts elsewhere.
 #[tauri::command]
 // TODO move this to a specific fabric commands file
 pub async fn set_context(app: AppHandle, context: String) -> Result<String, Error> {
-    run_fabric(app, format!("--context={}", context))
+    validate_name("Context", &context).map_err(invalid_input)?;
+
+    let overrides = active_overrides(app.clone())
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    if overrides.contexts_dir.is_some() {
+        return Err(invalid_input(
+            "fabric can't use contexts from this profile's contexts directory".to_string(),
+        ));
+    }
+
+    let mut env = profile_env(app.clone())
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    env.extend(
+        api_key_env(app)
+            .await
+            .map_err(|e| Error::Io(std::io::Error::other(e)))?,
+    );
+
+    let flag = format!("--context={}", context);
+    tauri::async_runtime::spawn_blocking(move || {
+        run_fabric_with_input(Path::new(DEFAULT_FABRIC_BIN), &[&flag], "", &env)
+    })
+    .await
+    .map_err(|e| Error::Io(std::io::Error::other(e.to_string())))?
+    .map_err(|e| Error::Io(std::io::Error::other(e)))
+}
+
+/// A context file with its metadata
+#[derive(Serialize)]
+pub struct ContextInfo {
+    name: String,
+    /// Size of the file in bytes
+    size: u64,
+    /// Words in the context, not counting its front matter
+    word_count: usize,
+    /// Last modification as seconds since the Unix epoch
+    modified: u64,
+    is_current: bool,
+    description: Option<String>,
+    tags: Vec<String>,
 }
 
-/// Lists all contexts in the Fabric system
+/// Lists the contexts in the contexts directory, sorted by name
+///
+/// Reads the directory directly, so it works without the fabric binary.
 ///
 /// ### Arguments
 ///
@@ -17,14 +74,64 @@ pub async fn set_context(app: AppHandle, context: String) -> Result<String, Erro
 ///
 /// ### Returns
 ///
-/// * `Result<String, Error>` - Success message on completion or error if operation fails
+/// * `Result<Vec<ContextInfo>, Error>` - The contexts with their metadata or error if the directory can't be read
 #[tauri::command]
-pub async fn list_contexts(app: AppHandle) -> Result<String, Error> {
-    run_fabric(app, "--listcontexts".to_string())
+pub async fn list_contexts(app: AppHandle) -> Result<Vec<ContextInfo>, Error> {
+    let contexts_dir = get_contexts_dir(app.clone()).await?;
+    if !contexts_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let current = load_secret(app, CURRENT_CONTEXT_KEY)
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?
+        .unwrap_or_default();
+    let current = current.trim();
+    let current = current.strip_suffix(".md").unwrap_or(current);
+
+    let mut contexts = Vec::new();
+    for entry in std::fs::read_dir(&contexts_dir)?.flatten() {
+        let path = entry.path();
+        let Some(name) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".md"))
+        else {
+            continue;
+        };
+        if name.starts_with('.') || !path.is_file() {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        let content = String::from_utf8_lossy(&std::fs::read(&path)?).into_owned();
+        let (front_matter, body) = split_front_matter(&content);
+        let front_matter = front_matter.unwrap_or_default();
+
+        contexts.push(ContextInfo {
+            name: name.to_string(),
+            size: metadata.len(),
+            word_count: body.split_whitespace().count(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            is_current: !current.is_empty() && name == current,
+            description: front_matter.description,
+            tags: front_matter.tags,
+        });
+    }
+    contexts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(contexts)
 }
 
 /// Removes all markdown text within a specific context.md file
 ///
+/// Works on the file directly, so a profile's contexts directory is honoured.
+///
 /// ### Arguments
 ///
 /// * `app` - The Tauri application handle for accessing app-wide state
@@ -35,11 +142,23 @@ pub async fn list_contexts(app: AppHandle) -> Result<String, Error> {
 /// * `Result<String, Error>` - Success message on completion or error if operation fails
 #[tauri::command]
 pub async fn wipe_context(app: AppHandle, context: String) -> Result<String, Error> {
-    run_fabric(app, format!("--wipecontext={}", context))
+    let context_path = get_context_path(app, &context).await?;
+    if !context_path.exists() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Context file does not exist",
+        )));
+    }
+
+    std::fs::write(&context_path, "").map_err(Error::Io)?;
+
+    Ok(format!("Wiped context: {}", context))
 }
 
 /// Prints the current context
 ///
+/// Reads the file directly, so a profile's contexts directory is honoured.
+///
 /// ### Arguments
 ///
 /// * `app` - The Tauri application handle for accessing app-wide state
@@ -50,7 +169,7 @@ pub async fn wipe_context(app: AppHandle, context: String) -> Result<String, Err
 /// * `Result<String, Error>` - Success message on completion or error if operation fails
 #[tauri::command]
 pub async fn print_context(app: AppHandle, context: String) -> Result<String, Error> {
-    run_fabric(app, format!("--printcontext={}", context))
+    read_context_file(app, context).await
 }
 
 /// Gets the directory path for the Fabric contexts
@@ -64,6 +183,14 @@ pub async fn print_context(app: AppHandle, context: String) -> Result<String, Er
 /// * `Result<PathBuf, Error>` - Path to the Fabric contexts directory or error if operation fails
 #[tauri::command]
 pub async fn get_contexts_dir(app: AppHandle) -> Result<PathBuf, Error> {
+    // The active profile can keep its contexts elsewhere
+    let overrides = active_overrides(app.clone())
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    if let Some(contexts_dir) = overrides.contexts_dir {
+        return Ok(contexts_dir);
+    }
+
     // Get the config directory path
     let mut env_path: std::path::PathBuf = app.path().home_dir().map_err(|_e| {
         Error::Io(std::io::Error::new(
@@ -79,6 +206,22 @@ pub async fn get_contexts_dir(app: AppHandle) -> Result<PathBuf, Error> {
     Ok(env_path)
 }
 
+/// Resolves the file of a context, rejecting titles that would lead outside the contexts directory
+async fn get_context_path(app: AppHandle, title: &str) -> Result<PathBuf, Error> {
+    let contexts_dir = get_contexts_dir(app).await?;
+
+    validate_name("Context", title)
+        .and_then(|_| safe_join(&contexts_dir, &format!("{}.md", title)))
+        .map_err(invalid_input)
+}
+
+fn invalid_input(message: String) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        message,
+    ))
+}
+
 /// Creates a new context.md file in the Fabric contexts directory
 ///
 /// ### Arguments
@@ -91,10 +234,7 @@ pub async fn get_contexts_dir(app: AppHandle) -> Result<PathBuf, Error> {
 /// * `Result<String, Error>` - Success message on completion or error if operation fails
 #[tauri::command]
 pub async fn create_context_file(app: AppHandle, title: String) -> Result<String, Error> {
-    let mut context_path = get_contexts_dir(app.clone()).await?;
-
-    // Rest of the function remains the same
-    context_path.push(format!("{}.md", title));
+    let context_path = get_context_path(app, &title).await?;
 
     if context_path.exists() {
         return Err(Error::Io(std::io::Error::new(
@@ -103,7 +243,7 @@ pub async fn create_context_file(app: AppHandle, title: String) -> Result<String
         )));
     }
 
-    std::fs::write(&context_path, "").map_err(|e| Error::Io(e))?;
+    std::fs::write(&context_path, "").map_err(Error::Io)?;
 
     Ok(format!("Created context file: {}", context_path.display()))
 }
@@ -120,8 +260,7 @@ pub async fn create_context_file(app: AppHandle, title: String) -> Result<String
 /// * `Result<String, Error>` - Content of the context.md file or error if operation fails
 #[tauri::command]
 pub async fn read_context_file(app: AppHandle, title: String) -> Result<String, Error> {
-    let mut context_path = get_contexts_dir(app.clone()).await?;
-    context_path.push(format!("{}.md", title));
+    let context_path = get_context_path(app, &title).await?;
 
     if !context_path.exists() {
         return Err(Error::Io(std::io::Error::new(
@@ -130,7 +269,7 @@ pub async fn read_context_file(app: AppHandle, title: String) -> Result<String,
         )));
     }
 
-    std::fs::read_to_string(&context_path).map_err(|e| Error::Io(e))
+    std::fs::read_to_string(&context_path).map_err(Error::Io)
 }
 
 /// Saves the content to a specific context.md file
@@ -150,8 +289,7 @@ pub async fn save_context_file(
     title: String,
     content: String,
 ) -> Result<String, Error> {
-    let mut context_path = get_contexts_dir(app.clone()).await?;
-    context_path.push(format!("{}.md", title));
+    let context_path = get_context_path(app, &title).await?;
 
     if !context_path.exists() {
         return Err(Error::Io(std::io::Error::new(
@@ -160,7 +298,7 @@ pub async fn save_context_file(
         )));
     }
 
-    std::fs::write(&context_path, content).map_err(|e| Error::Io(e))?;
+    std::fs::write(&context_path, content).map_err(Error::Io)?;
     Ok("Context saved successfully".to_string())
 }
 
@@ -176,8 +314,7 @@ pub async fn save_context_file(
 /// * `Result<String, Error>` - Success message on completion or error if operation fails
 #[tauri::command]
 pub async fn delete_context_file(app: AppHandle, title: String) -> Result<String, Error> {
-    let mut context_path = get_contexts_dir(app.clone()).await?;
-    context_path.push(format!("{}.md", title));
+    let context_path = get_context_path(app, &title).await?;
 
     if !context_path.exists() {
         return Err(Error::Io(std::io::Error::new(
@@ -186,16 +323,163 @@ pub async fn delete_context_file(app: AppHandle, title: String) -> Result<String
         )));
     }
 
-    std::fs::remove_file(&context_path).map_err(|e| Error::Io(e))?;
+    std::fs::remove_file(&context_path).map_err(Error::Io)?;
     Ok(format!("Context file '{}' deleted successfully", title))
 }
 
+/// Whether two paths name the same existing file, e.g. when only the case differs
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Renames a context, keeping it current if it was
+///
+/// The file is renamed while the .env file is locked, and renamed back if
+/// `CURRENT_CONTEXT` couldn't be updated.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `title` - The name of the context to rename
+/// * `new_title` - The new name, which must not be taken by another context
+///
+/// ### Returns
+///
+/// * `Result<String, Error>` - Success message on completion or error if operation fails
+#[tauri::command]
+pub async fn rename_context(
+    app: AppHandle,
+    title: String,
+    new_title: String,
+) -> Result<String, Error> {
+    let new_title = new_title.trim().to_string();
+    let context_path = get_context_path(app.clone(), &title).await?;
+    let new_path = get_context_path(app.clone(), &new_title).await?;
+
+    if !context_path.exists() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Context file does not exist",
+        )));
+    }
+    if new_path.exists() && !is_same_file(&context_path, &new_path) {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("A context named '{}' already exists", new_title),
+        )));
+    }
+
+    let env_path = get_env_file_path(app)
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    let mut renamed = false;
+    let result = EnvFile::update(&env_path, |env| {
+        std::fs::rename(&context_path, &new_path)
+            .map_err(|e| format!("Failed to rename context: {}", e))?;
+        renamed = true;
+
+        // fabric accepts the context with or without its extension
+        let current = env.get(CURRENT_CONTEXT_KEY).unwrap_or_default();
+        if current == title {
+            env.set(CURRENT_CONTEXT_KEY, &new_title);
+        } else if current == format!("{}.md", title) {
+            env.set(CURRENT_CONTEXT_KEY, &format!("{}.md", new_title));
+        }
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        if renamed {
+            let _ = std::fs::rename(&new_path, &context_path);
+        }
+        return Err(Error::Io(std::io::Error::other(e)));
+    }
+
+    Ok(format!("Context '{}' renamed to '{}'", title, new_title))
+}
+
+/// Copies a context under a new name
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `title` - The name of the context to copy
+/// * `new_title` - The name of the copy, defaults to the first free `<title> copy`, `<title> copy 2`, ...
+///
+/// ### Returns
+///
+/// * `Result<String, Error>` - The name of the copy or error if operation fails
+#[tauri::command]
+pub async fn duplicate_context(
+    app: AppHandle,
+    title: String,
+    new_title: Option<String>,
+) -> Result<String, Error> {
+    let context_path = get_context_path(app.clone(), &title).await?;
+    if !context_path.exists() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Context file does not exist",
+        )));
+    }
+
+    let (new_title, new_path) = match new_title.map(|t| t.trim().to_string()) {
+        Some(new_title) => {
+            let new_path = get_context_path(app.clone(), &new_title).await?;
+            if new_path.exists() {
+                return Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("A context named '{}' already exists", new_title),
+                )));
+            }
+            (new_title, new_path)
+        }
+        None => {
+            let mut copy = 1;
+            loop {
+                let candidate = match copy {
+                    1 => format!("{} copy", title),
+                    n => format!("{} copy {}", title, n),
+                };
+                let candidate_path = get_context_path(app.clone(), &candidate).await?;
+                if !candidate_path.exists() {
+                    break (candidate, candidate_path);
+                }
+                copy += 1;
+            }
+        }
+    };
+
+    // create_new guards against a context appearing since the check above
+    let mut source = std::fs::File::open(&context_path).map_err(Error::Io)?;
+    let mut target = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&new_path)
+        .map_err(Error::Io)?;
+    std::io::copy(&mut source, &mut target).map_err(Error::Io)?;
+
+    Ok(new_title)
+}
+
 #[tauri::command]
 pub async fn set_current_context(app: AppHandle, context: String) -> Result<String, Error> {
+    // An empty context clears the current one
+    if !context.is_empty() {
+        validate_name("Context", &context).map_err(invalid_input)?;
+    }
+
     // Update the CURRENT_CONTEXT in .env file
-    update_secret(app.clone(), "CURRENT_CONTEXT".to_string(), context.clone())
-        .await
-        .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+    update_secret(
+        app.clone(),
+        CURRENT_CONTEXT_KEY.to_string(),
+        context.clone(),
+    )
+    .await
+    .map_err(|e| Error::Io(std::io::Error::other(e)))?;
 
     Ok("Current context set successfully".to_string())
 }
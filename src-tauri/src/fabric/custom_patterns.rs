@@ -0,0 +1,136 @@
+use crate::fabric::names::pattern_dir;
+use crate::fabric::paths::{get_custom_patterns_dir, get_patterns_dir, path_to_string};
+use crate::fabric::pattern_history::record_pattern_version;
+use crate::fabric::pattern_lint::{
+    describe_errors, has_errors, lint_pattern_files, list_pattern_names, Diagnostic,
+};
+use crate::fabric::patterns::{read_pattern_content, PatternContent, SYSTEM_FILE, USER_FILE};
+use crate::fabric::secrets::{get_secret, update_secret};
+use std::fs;
+use tauri::AppHandle;
+
+/// Lists the patterns in the custom patterns directory
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<String>, String>` - Names of the custom patterns or error if operation fails
+#[tauri::command]
+pub async fn get_custom_patterns(app: AppHandle) -> Result<Vec<String>, String> {
+    let custom_dir = get_custom_patterns_dir(app).await?;
+    Ok(list_pattern_names(&custom_dir))
+}
+
+/// Reads the system.md and user.md of a custom pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the custom pattern
+///
+/// ### Returns
+///
+/// * `Result<PatternContent, String>` - The prompt files or error if operation fails
+#[tauri::command]
+pub async fn read_custom_pattern(app: AppHandle, name: String) -> Result<PatternContent, String> {
+    let pattern_dir = pattern_dir(&get_custom_patterns_dir(app).await?, &name)?;
+    if !pattern_dir.is_dir() {
+        return Err(format!("Custom pattern '{}' does not exist", name));
+    }
+
+    read_pattern_content(&pattern_dir)
+}
+
+/// Lints and saves the prompt files of a custom pattern, creating it if needed
+///
+/// The save is rejected when the linter reports any error, which includes
+/// reusing the name of a built-in pattern.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the custom pattern
+/// * `system` - The content of the system.md file
+/// * `user` - The content of the user.md file, an empty string removes it and `None` leaves it untouched
+///
+/// ### Returns
+///
+/// * `Result<Vec<Diagnostic>, String>` - Remaining warnings on success, or the lint errors if the save was blocked
+#[tauri::command]
+pub async fn save_custom_pattern(
+    app: AppHandle,
+    name: String,
+    system: String,
+    user: Option<String>,
+) -> Result<Vec<Diagnostic>, String> {
+    let name = name.trim().to_string();
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
+    let custom_dir = get_custom_patterns_dir(app.clone()).await?;
+    let pattern_dir = pattern_dir(&custom_dir, &name)?;
+
+    // Lint the files as they will be on disk after the save
+    let user = match user {
+        Some(user) if user.trim().is_empty() => None,
+        Some(user) => Some(user),
+        None => fs::read_to_string(pattern_dir.join(USER_FILE)).ok(),
+    };
+    let mut files = vec![(SYSTEM_FILE.to_string(), system.clone())];
+    if let Some(user) = &user {
+        files.push((USER_FILE.to_string(), user.clone()));
+    }
+
+    let shadows_builtin = custom_dir != patterns_dir && patterns_dir.join(&name).is_dir();
+    let diagnostics = lint_pattern_files(&name, &files, shadows_builtin);
+
+    if has_errors(&diagnostics) {
+        return Err(describe_errors(&name, &diagnostics));
+    }
+
+    // Files edited outside the app would otherwise be lost from the history
+    if pattern_dir.exists() {
+        record_pattern_version(&pattern_dir)?;
+    }
+
+    fs::create_dir_all(&pattern_dir)
+        .map_err(|e| format!("Failed to create pattern directory: {}", e))?;
+    fs::write(pattern_dir.join(SYSTEM_FILE), system)
+        .map_err(|e| format!("Failed to write {}: {}", SYSTEM_FILE, e))?;
+
+    let user_path = pattern_dir.join(USER_FILE);
+    match user {
+        Some(user) => fs::write(&user_path, user)
+            .map_err(|e| format!("Failed to write {}: {}", USER_FILE, e))?,
+        None if user_path.exists() => fs::remove_file(&user_path)
+            .map_err(|e| format!("Failed to remove {}: {}", USER_FILE, e))?,
+        None => {}
+    }
+    record_pattern_version(&pattern_dir)?;
+
+    ensure_custom_patterns_configured(app).await?;
+
+    Ok(diagnostics)
+}
+
+/// Makes sure fabric itself knows where to find custom patterns
+///
+/// Writes the resolved custom patterns directory to `CUSTOM_PATTERNS_DIRECTORY`
+/// if the .env file doesn't set it yet.
+pub async fn ensure_custom_patterns_configured(app: AppHandle) -> Result<(), String> {
+    let configured = get_secret(app.clone(), "CUSTOM_PATTERNS_DIRECTORY".to_string())
+        .await
+        .unwrap_or_default();
+    if !configured.trim().is_empty() {
+        return Ok(());
+    }
+
+    let custom_dir = get_custom_patterns_dir(app.clone()).await?;
+    update_secret(
+        app,
+        "CUSTOM_PATTERNS_DIRECTORY".to_string(),
+        path_to_string(custom_dir)?,
+    )
+    .await
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Serialize)]
+pub struct FileDiff {
+    file: String,
+    diff: String,
+}
+
+/// Builds unified diffs for every file that differs between two sets of files
+///
+/// ### Arguments
+///
+/// * `old` - File paths and contents of the old side
+/// * `new` - File paths and contents of the new side
+/// * `old_label` - Prefix for the old side in the diff headers
+/// * `new_label` - Prefix for the new side in the diff headers
+pub fn diff_files(
+    old: &BTreeMap<String, Vec<u8>>,
+    new: &BTreeMap<String, Vec<u8>>,
+    old_label: &str,
+    new_label: &str,
+) -> Vec<FileDiff> {
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    names
+        .into_iter()
+        .filter(|name| old.get(*name) != new.get(*name))
+        .map(|name| {
+            let old_text = old
+                .get(name)
+                .map(|c| String::from_utf8_lossy(c).into_owned())
+                .unwrap_or_default();
+            let new_text = new
+                .get(name)
+                .map(|c| String::from_utf8_lossy(c).into_owned())
+                .unwrap_or_default();
+
+            let diff = TextDiff::from_lines(&old_text, &new_text)
+                .unified_diff()
+                .header(
+                    &format!("{}/{}", old_label, name),
+                    &format!("{}/{}", new_label, name),
+                )
+                .to_string();
+
+            FileDiff {
+                file: name.clone(),
+                diff,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,607 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serialises read-modify-write cycles of .env files within the app
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// One logical line of a .env file, kept with its original text so unedited
+/// lines are written back exactly as they were read
+enum Line {
+    /// Comments, blank lines and anything that isn't an assignment
+    Other(String),
+    Entry {
+        key: String,
+        value: String,
+        exported: bool,
+        /// The original text, including continuation lines and the line ending
+        raw: String,
+    },
+}
+
+/// A parsed .env file
+///
+/// Understands comments, blank lines, `export` prefixes, single and double
+/// quotes, escapes in double quotes and quoted values spanning several lines.
+/// Rendering gives back the original bytes except for the keys that were set.
+#[derive(Default)]
+pub struct EnvFile {
+    lines: Vec<Line>,
+}
+
+/// Splits `text` into physical lines, each keeping its line ending
+fn physical_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn line_ending(raw: &str) -> &'static str {
+    if raw.ends_with("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+/// Splits `KEY=rest` (after an optional `export`) into its parts
+fn split_assignment(line: &str) -> Option<(String, bool, &str)> {
+    let mut rest = line.trim_start();
+    let mut exported = false;
+    if let Some(after) = rest.strip_prefix("export") {
+        if after.starts_with([' ', '\t']) {
+            rest = after.trim_start();
+            exported = true;
+        }
+    }
+
+    let key_len = rest.find(|c: char| !is_key_char(c)).unwrap_or(rest.len());
+    let key = &rest[..key_len];
+    if key.is_empty() || key.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let value = rest[key_len..].trim_start().strip_prefix('=')?;
+    Some((
+        key.to_string(),
+        exported,
+        value.trim_start_matches([' ', '\t']),
+    ))
+}
+
+/// Reads a quoted value that may continue over the following physical lines
+///
+/// ### Returns
+///
+/// * `Option<(String, usize)>` - The unescaped value and how many extra lines it used, `None` if the quote is never closed
+fn read_quoted(first: &str, following: &[&str], quote: char) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut escaped = false;
+
+    for (extra, text) in std::iter::once(first)
+        .chain(following.iter().copied())
+        .enumerate()
+    {
+        for c in text.chars() {
+            if quote == '"' && escaped {
+                match c {
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    other => value.push(other),
+                }
+                escaped = false;
+            } else if quote == '"' && c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                return Some((value, extra));
+            } else {
+                value.push(c);
+            }
+        }
+    }
+
+    None
+}
+
+/// Reads an unquoted value, dropping a trailing ` # comment`
+///
+/// A `#` that isn't preceded by whitespace is part of the value, e.g. `KEY=abc#123`.
+fn read_unquoted(text: &str) -> String {
+    let text = text.trim_end_matches(['\r', '\n']);
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| c == '#' && text[..i].ends_with([' ', '\t']))
+        .map_or(text.len(), |(i, _)| i);
+
+    text[..end].trim().to_string()
+}
+
+/// Whether a value can be written without quotes
+fn is_bare(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,/:@+=~%".contains(c))
+}
+
+/// Formats a value so that parsing it gives back the same string
+fn quote(value: &str) -> String {
+    if is_bare(value) {
+        return value.to_string();
+    }
+
+    // Single quotes are literal, so use them whenever they can hold the value
+    if !value.contains(['\'', '\n', '\r']) {
+        return format!("'{}'", value);
+    }
+
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '$' => quoted.push_str("\\$"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl EnvFile {
+    /// Parses the contents of a .env file
+    pub fn parse(text: &str) -> Self {
+        let physical = physical_lines(text);
+        let mut lines = Vec::new();
+        let mut i = 0;
+
+        while i < physical.len() {
+            let line = physical[i];
+            let trimmed = line.trim();
+
+            let assignment = if trimmed.is_empty() || trimmed.starts_with('#') {
+                None
+            } else {
+                split_assignment(line)
+            };
+
+            let Some((key, exported, rest)) = assignment else {
+                lines.push(Line::Other(line.to_string()));
+                i += 1;
+                continue;
+            };
+
+            let quoted = rest
+                .chars()
+                .next()
+                .filter(|c| *c == '"' || *c == '\'')
+                .and_then(|q| read_quoted(&rest[1..], &physical[i + 1..], q));
+
+            // An unterminated quote is read as a plain value on its own line
+            let (value, extra) = quoted.unwrap_or_else(|| (read_unquoted(rest), 0));
+            let raw = physical[i..=i + extra].concat();
+
+            lines.push(Line::Entry {
+                key,
+                value,
+                exported,
+                raw,
+            });
+            i += extra + 1;
+        }
+
+        EnvFile { lines }
+    }
+
+    /// Reads and parses a .env file
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|_| "Could not read .env file".to_string())?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Gets the value of a key, the last assignment wins like it does for fabric
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().rev().find_map(|line| match line {
+            Line::Entry { key: k, value, .. } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Lists every key with its effective value, in the order the keys first appear
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        let mut entries: Vec<(&str, &str)> = Vec::new();
+        for line in &self.lines {
+            if let Line::Entry { key, .. } = line {
+                if !entries.iter().any(|(k, _)| k == key) {
+                    entries.push((key, self.get(key).unwrap_or_default()));
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Sets a key, rewriting only its own line or appending it at the end
+    pub fn set(&mut self, key: &str, value: &str) {
+        let existing = self.lines.iter_mut().rev().find_map(|line| match line {
+            Line::Entry {
+                key: k,
+                value: v,
+                exported,
+                raw,
+            } if k == key => Some((v, *exported, raw)),
+            _ => None,
+        });
+
+        match existing {
+            Some((current, _, _)) if current == value => {}
+            Some((current, exported, raw)) => {
+                let prefix = if exported { "export " } else { "" };
+                *raw = format!("{}{}={}{}", prefix, key, quote(value), line_ending(raw));
+                *current = value.to_string();
+            }
+            None => {
+                // Make sure the new entry starts on its own line
+                if let Some(last) = self.lines.last_mut() {
+                    let raw = match last {
+                        Line::Other(raw) => raw,
+                        Line::Entry { raw, .. } => raw,
+                    };
+                    if !raw.ends_with('\n') {
+                        raw.push('\n');
+                    }
+                }
+
+                self.lines.push(Line::Entry {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    exported: false,
+                    raw: format!("{}={}\n", key, quote(value)),
+                });
+            }
+        }
+    }
+
+    /// Removes every assignment of a key, leaving the other lines untouched
+    ///
+    /// ### Returns
+    ///
+    /// * `bool` - Whether the key was present
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(line, Line::Entry { key: k, .. } if k == key));
+
+        self.lines.len() != before
+    }
+
+    /// Renders the file, unchanged lines byte-for-byte as they were read
+    pub fn render(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| match line {
+                Line::Other(raw) => raw.as_str(),
+                Line::Entry { raw, .. } => raw.as_str(),
+            })
+            .collect()
+    }
+
+    /// Writes the file atomically, creating its directory if needed
+    ///
+    /// The content goes to a temporary file next to the target which then
+    /// replaces it, so a crash can never leave a truncated file behind. An
+    /// existing file keeps its permissions, a new one is only readable by the user.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let parent = path
+            .parent()
+            .ok_or_else(|| "Invalid .env file path".to_string())?;
+        fs::create_dir_all(parent).map_err(|_| "Could not create config directory".to_string())?;
+
+        replace_file(path, self.render().as_bytes(), true)
+            .map_err(|e| format!("Could not write to .env file: {}", e))
+    }
+
+    /// Reads, edits and writes a .env file while holding its lock
+    ///
+    /// Updates are serialised within the app and, through an advisory lock on
+    /// `<file>.lock`, with other processes that honour it. A missing file is
+    /// treated as empty.
+    ///
+    /// ### Arguments
+    ///
+    /// * `path` - The .env file to edit
+    /// * `edit` - Changes the parsed file, returning an error leaves it untouched
+    pub fn update<T>(
+        path: &Path,
+        edit: impl FnOnce(&mut EnvFile) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let _guard = ENV_LOCK
+            .lock()
+            .map_err(|_| "Failed to lock .env file".to_string())?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|_| "Could not create config directory".to_string())?;
+        }
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| format!("Could not open .env lock file: {}", e))?;
+        lock_file
+            .lock()
+            .map_err(|e| format!("Could not lock .env file: {}", e))?;
+
+        let mut env = if path.exists() {
+            Self::read(path)?
+        } else {
+            Self::default()
+        };
+        let result = edit(&mut env)?;
+        env.write(path)?;
+
+        Ok(result)
+    }
+}
+
+impl fmt::Display for EnvFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+/// Writes a file atomically so that only the user can read it, creating its directory if needed
+///
+/// Used for files that may hold secrets, like restored config files and backups.
+pub fn write_private_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    replace_file(path, content, false)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Replaces a file through a temporary file next to it
+///
+/// The new file is only readable by the user, unless `keep_permissions` is
+/// set and the file already exists.
+fn replace_file(path: &Path, content: &[u8], keep_permissions: bool) -> io::Result<()> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = parent.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = write_new_file(&temp_path, content)
+        .and_then(|()| match fs::metadata(path) {
+            Ok(metadata) if keep_permissions => {
+                fs::set_permissions(&temp_path, metadata.permissions())
+            }
+            _ => Ok(()),
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Creates a file that doesn't exist yet, readable only by the user on unix
+fn write_new_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# fabric settings\n\
+        \n\
+        DEFAULT_VENDOR=OpenAI\n\
+        export DEFAULT_MODEL=gpt-4o   # inline comment\n\
+        SINGLE='$HOME # stays literal'\n\
+        DOUBLE=\"tab\\there \\\"quoted\\\"\"\n\
+        HASH=abc#123\n\
+        MULTI=\"first\n\
+        second\"\n\
+        EMPTY=\n";
+
+    fn assert_round_trip(text: &str) {
+        assert_eq!(EnvFile::parse(text).to_string(), text);
+    }
+
+    #[test]
+    fn round_trips_comments_blank_lines_and_export() {
+        assert_round_trip("# comment\n\n   \nexport KEY=value\n  # indented\n");
+    }
+
+    #[test]
+    fn round_trips_quotes_and_escapes() {
+        assert_round_trip(
+            "A='single # not a comment'\nB=\"a\\nb \\\"c\\\" \\\\\"\nC=\"unterminated\n",
+        );
+    }
+
+    #[test]
+    fn round_trips_multiline_values() {
+        assert_round_trip("KEY=\"line one\nline two\"\nNEXT=1\n");
+    }
+
+    #[test]
+    fn round_trips_crlf() {
+        assert_round_trip("# comment\r\nA=1\r\nexport B='two'\r\n\r\n");
+    }
+
+    #[test]
+    fn round_trips_without_trailing_newline() {
+        assert_round_trip("A=1\nB=2");
+    }
+
+    #[test]
+    fn round_trips_sample() {
+        assert_round_trip(SAMPLE);
+    }
+
+    #[test]
+    fn parses_values() {
+        let env = EnvFile::parse(SAMPLE);
+        assert_eq!(env.get("DEFAULT_VENDOR"), Some("OpenAI"));
+        assert_eq!(env.get("DEFAULT_MODEL"), Some("gpt-4o"));
+        assert_eq!(env.get("SINGLE"), Some("$HOME # stays literal"));
+        assert_eq!(env.get("DOUBLE"), Some("tab\there \"quoted\""));
+        assert_eq!(env.get("HASH"), Some("abc#123"));
+        assert_eq!(env.get("MULTI"), Some("first\nsecond"));
+        assert_eq!(env.get("EMPTY"), Some(""));
+        assert_eq!(EnvFile::parse("A=1\r\n").get("A"), Some("1"));
+    }
+
+    #[test]
+    fn set_changes_only_its_own_line() {
+        let mut env = EnvFile::parse(SAMPLE);
+        env.set("DEFAULT_MODEL", "claude 3");
+
+        let before: Vec<&str> = SAMPLE.lines().collect();
+        let rendered = env.to_string();
+        let after: Vec<&str> = rendered.lines().collect();
+        assert_eq!(before.len(), after.len());
+        for (i, (old, new)) in before.iter().zip(&after).enumerate() {
+            if i == 3 {
+                assert_eq!(*new, "export DEFAULT_MODEL='claude 3'");
+            } else {
+                assert_eq!(old, new);
+            }
+        }
+        assert_eq!(
+            EnvFile::parse(&rendered).get("DEFAULT_MODEL"),
+            Some("claude 3")
+        );
+    }
+
+    #[test]
+    fn set_keeps_crlf_and_appends_on_a_new_line() {
+        let mut env = EnvFile::parse("A=1\r\nB=2");
+        env.set("A", "x y");
+        env.set("C", "3");
+        assert_eq!(env.to_string(), "A='x y'\r\nB=2\nC=3\n");
+    }
+
+    #[test]
+    fn values_survive_quoting() {
+        for value in [
+            "plain",
+            "with space",
+            "it's",
+            "line\nbreak",
+            "\"$HOME\"\\",
+            "tab\t",
+        ] {
+            let mut env = EnvFile::default();
+            env.set("KEY", value);
+            assert_eq!(EnvFile::parse(&env.to_string()).get("KEY"), Some(value));
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod unix_tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn mode(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    fn temp_files(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.ends_with(".tmp"))
+            .collect()
+    }
+
+    #[test]
+    fn a_new_env_file_is_readable_only_by_the_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles").join("work").join(".env");
+
+        EnvFile::update(&path, |env| {
+            env.set("OPENAI_API_KEY", "sk-secret");
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(mode(&path), 0o600);
+    }
+
+    #[test]
+    fn an_existing_env_file_keeps_its_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        fs::write(&path, "DEFAULT_MODEL=gpt-4o\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        EnvFile::update(&path, |env| {
+            env.set("DEFAULT_MODEL", "gpt-4.1");
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "DEFAULT_MODEL=gpt-4.1\n"
+        );
+        assert_eq!(mode(&path), 0o640);
+    }
+
+    #[test]
+    fn private_files_replace_existing_ones_readable_only_by_the_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions").join("chat.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private_file(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn a_failed_write_leaves_no_temporary_file() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory in the way makes the final rename fail
+        let path = dir.path().join(".env");
+        fs::create_dir_all(path.join("blocker")).unwrap();
+
+        assert!(replace_file(&path, b"DEFAULT_MODEL=gpt-4o\n", true).is_err());
+        assert!(write_private_file(&path, b"DEFAULT_MODEL=gpt-4o\n").is_err());
+        assert_eq!(temp_files(dir.path()), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,222 @@
+use serde::Serialize;
+
+/// A top-level section of a Markdown document
+#[derive(Serialize, Clone)]
+pub struct Section {
+    pub heading: String,
+    pub content: String,
+}
+
+/// Strips a surrounding Markdown code fence such as ```json ... ```
+pub fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+/// Splits a Markdown document into its `#` headed sections
+///
+/// Text before the first heading is returned as a section with an empty heading.
+/// Headings inside code fences are ignored.
+pub fn split_sections(text: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut current = Section {
+        heading: String::new(),
+        content: String::new(),
+    };
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+
+        if !in_fence && trimmed.starts_with("# ") {
+            if !current.heading.is_empty() || !current.content.trim().is_empty() {
+                sections.push(current);
+            }
+            current = Section {
+                heading: trimmed.trim_start_matches('#').trim().to_string(),
+                content: String::new(),
+            };
+            continue;
+        }
+
+        current.content.push_str(line);
+        current.content.push('\n');
+    }
+
+    if !current.heading.is_empty() || !current.content.trim().is_empty() {
+        sections.push(current);
+    }
+
+    for section in &mut sections {
+        section.content = section.content.trim().to_string();
+    }
+
+    sections
+}
+
+/// Gets the first line of a pattern's purpose, which fabric patterns use as their description
+///
+/// Falls back to the first non-empty section when there is no IDENTITY and PURPOSE heading.
+pub fn purpose_line(sections: &[Section]) -> String {
+    sections
+        .iter()
+        .find(|s| s.heading.to_uppercase().contains("PURPOSE"))
+        .or_else(|| sections.iter().find(|s| !s.content.is_empty()))
+        .and_then(|s| s.content.lines().find(|l| !l.trim().is_empty()))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// The fields of a YAML front-matter block the app understands
+#[derive(Serialize, Default)]
+pub struct FrontMatter {
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Strips the quotes around a YAML scalar
+fn yaml_scalar(value: &str) -> &str {
+    let value = value.trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+        .unwrap_or(value)
+}
+
+/// Reads a YAML list written inline (`[a, b]`) or as a comma-separated string
+fn yaml_inline_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(value);
+
+    value
+        .split(',')
+        .map(yaml_scalar)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Splits a `---` delimited YAML front-matter block off a Markdown document
+///
+/// Only `description` and `tags` are read, either as an inline or a `- item`
+/// list; anything else in the block is ignored rather than fully parsed.
+///
+/// ### Returns
+///
+/// * `(Option<FrontMatter>, &str)` - The front matter, if there is a closed block, and the rest of the document
+pub fn split_front_matter(text: &str) -> (Option<FrontMatter>, &str) {
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (None, text);
+    };
+
+    let mut front_matter = FrontMatter::default();
+    let mut in_tags = false;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim_end();
+        if line == "---" || line == "..." {
+            return (Some(front_matter), &rest[offset..]);
+        }
+
+        if in_tags {
+            if let Some(item) = line.trim_start().strip_prefix("- ") {
+                front_matter.tags.push(yaml_scalar(item).to_string());
+                continue;
+            }
+            in_tags = false;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "description" => {
+                let value = yaml_scalar(value);
+                if !value.is_empty() {
+                    front_matter.description = Some(value.to_string());
+                }
+            }
+            "tags" if value.trim().is_empty() => in_tags = true,
+            "tags" => front_matter.tags = yaml_inline_list(value),
+            _ => {}
+        }
+    }
+
+    // Without a closing delimiter the dashes are a horizontal rule, not front matter
+    (None, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn front_matter(text: &str) -> (Option<String>, Vec<String>, &str) {
+        let (front_matter, rest) = split_front_matter(text);
+        let front_matter = front_matter.expect("front matter");
+        (front_matter.description, front_matter.tags, rest)
+    }
+
+    #[test]
+    fn text_without_front_matter_is_left_alone() {
+        for text in [
+            "# IDENTITY\n",
+            "",
+            "--- not a delimiter\n---\n",
+            "\n---\ntags: a\n---\n",
+        ] {
+            let (front_matter, rest) = split_front_matter(text);
+            assert!(front_matter.is_none(), "{:?}", text);
+            assert_eq!(rest, text);
+        }
+    }
+
+    #[test]
+    fn an_unterminated_block_is_not_front_matter() {
+        let text = "---\ndescription: Summarize\n# IDENTITY\n";
+        let (front_matter, rest) = split_front_matter(text);
+        assert!(front_matter.is_none());
+        assert_eq!(rest, text);
+    }
+
+    #[test]
+    fn crlf_line_endings_are_understood() {
+        let (description, tags, rest) = front_matter(
+            "---\r\ndescription: \"Summarize\"\r\ntags: [writing, summaries]\r\n---\r\n# IDENTITY\r\n",
+        );
+        assert_eq!(description.as_deref(), Some("Summarize"));
+        assert_eq!(tags, vec!["writing", "summaries"]);
+        assert_eq!(rest, "# IDENTITY\r\n");
+    }
+
+    #[test]
+    fn tags_can_be_a_list_or_a_scalar() {
+        let (description, tags, rest) = front_matter(
+            "---\ntags:\n  - writing\n  - 'summaries'\ndescription: Summarize\n---\n# IDENTITY\n",
+        );
+        assert_eq!(description.as_deref(), Some("Summarize"));
+        assert_eq!(tags, vec!["writing", "summaries"]);
+        assert_eq!(rest, "# IDENTITY\n");
+
+        let (_, tags, _) = front_matter("---\ntags: writing\n---\n");
+        assert_eq!(tags, vec!["writing"]);
+
+        let (_, tags, _) = front_matter("---\ntags: writing, 'summaries'\n...\n");
+        assert_eq!(tags, vec!["writing", "summaries"]);
+    }
+}
@@ -1,14 +1,26 @@
 pub mod patterns;
 pub use patterns::{
-    get_default_pattern, get_fabric_dir, get_patterns, get_patterns_git_folder,
-    get_patterns_git_repo, get_selected_pattern, set_default_pattern, set_patterns_git_folder,
-    set_selected_pattern, update_patterns,
+    get_fabric_dir, get_patterns, get_patterns_git_folder, get_patterns_git_repo,
+    set_patterns_git_folder, update_patterns,
 };
 
+pub mod pattern_selection;
+pub use pattern_selection::{
+    get_default_pattern, get_selected_pattern, set_default_pattern, set_selected_pattern,
+};
+
+pub mod dotenv;
+
+pub mod profiles;
+pub use profiles::{clone_profile, create_profile, delete_profile, list_profiles, switch_profile};
+
+pub mod secret_store;
+pub use secret_store::{get_secret_backend, set_secret_backend};
+
 pub mod secrets;
 pub use secrets::{
-    get_api_keys, get_base_urls, get_env_file_path, get_secret, get_secrets, reset_secret,
-    update_secret,
+    delete_secret, get_api_keys, get_base_urls, get_env_file_path, get_secret, get_secrets,
+    reset_secret, reveal_secret, update_secret, update_secrets,
 };
 
 pub mod run;
@@ -20,6 +32,9 @@ pub use run::{
 pub mod install;
 pub use install::install_fabric;
 
+pub mod setup;
+pub use setup::*;
+
 pub mod settings;
 pub use settings::*;
 
@@ -31,3 +46,40 @@ pub use contexts::*;
 
 pub mod paths;
 pub use paths::*;
+
+pub mod archive;
+pub mod diff;
+pub mod markdown;
+pub mod names;
+
+pub mod snapshots;
+pub use snapshots::*;
+
+pub mod pattern_lint;
+pub use pattern_lint::lint_patterns;
+
+pub mod custom_patterns;
+pub use custom_patterns::*;
+
+pub mod pattern_packs;
+pub use pattern_packs::*;
+
+pub mod pattern_history;
+pub use pattern_history::*;
+
+pub mod pattern_tests;
+pub use pattern_tests::*;
+
+pub mod watcher;
+
+pub mod pattern_sources;
+pub use pattern_sources::{get_pattern_sources, set_pattern_sources};
+
+pub mod pattern_generator;
+pub use pattern_generator::*;
+
+pub mod pattern_suggestions;
+pub use pattern_suggestions::suggest_patterns;
+
+pub mod config_backup;
+pub use config_backup::{backup_config, preview_config_restore, restore_config};
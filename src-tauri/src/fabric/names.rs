@@ -0,0 +1,191 @@
+use std::path::{Path, PathBuf};
+
+/// Longest name accepted in bytes, leaving room for extensions within the 255 byte file name limit
+pub const MAX_NAME_LEN: usize = 128;
+
+/// Names Windows reserves for devices, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters that separate paths or aren't allowed in file names on some platform
+const FORBIDDEN_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Checks that a user-given name of a context, pattern or session is safe to use as a file name
+///
+/// ### Arguments
+///
+/// * `kind` - What is being named, used in the error message, e.g. `"Context"`
+/// * `name` - The name to check
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result if the name is safe, or why it isn't
+pub fn validate_name(kind: &str, name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(format!("{} name cannot be empty", kind));
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "{} name cannot be longer than {} bytes",
+            kind, MAX_NAME_LEN
+        ));
+    }
+    if name.contains(FORBIDDEN_CHARS) || name.chars().any(char::is_control) {
+        return Err(format!(
+            "{} name '{}' cannot contain path separators or any of : * ? \" < > |",
+            kind, name
+        ));
+    }
+    // Covers `.` and `..` as well as hidden files like `.env`
+    if name.starts_with('.') {
+        return Err(format!("{} name '{}' cannot start with '.'", kind, name));
+    }
+    // Windows silently drops these, so the name would refer to another file
+    if name.ends_with(['.', ' ']) || name.starts_with(' ') {
+        return Err(format!(
+            "{} name '{}' cannot start with a space or end with '.' or a space",
+            kind, name
+        ));
+    }
+
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        return Err(format!("'{}' is a reserved name", name));
+    }
+
+    Ok(())
+}
+
+/// Joins a file name onto a directory, making sure the result stays inside it
+///
+/// Both paths are canonicalised, so symlinks that lead out of `dir` are
+/// rejected as well. A path that doesn't exist yet is checked through its parent.
+///
+/// ### Arguments
+///
+/// * `dir` - The directory the file must be in
+/// * `file_name` - A name that already passed [`validate_name`], with any extension added
+///
+/// ### Returns
+///
+/// * `Result<PathBuf, String>` - The joined path, or error if it resolves outside `dir`
+pub fn safe_join(dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    let path = dir.join(file_name);
+    if path.parent() != Some(dir) {
+        return Err(format!("'{}' is not a plain file name", file_name));
+    }
+
+    // Nothing can escape a directory that doesn't exist yet
+    let Ok(canonical_dir) = dir.canonicalize() else {
+        return Ok(path);
+    };
+
+    let resolved = match path.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(_) if path.symlink_metadata().is_err() => return Ok(path),
+        // A dangling symlink
+        Err(e) => return Err(format!("Could not resolve '{}': {}", file_name, e)),
+    };
+    if resolved.parent() != Some(canonical_dir.as_path()) {
+        return Err(format!(
+            "'{}' resolves outside of {}",
+            file_name,
+            dir.display()
+        ));
+    }
+
+    Ok(path)
+}
+
+/// Validates a pattern name and resolves its directory inside a patterns directory
+pub fn pattern_dir(patterns_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_name("Pattern", name)?;
+    safe_join(patterns_dir, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn accepts_ordinary_names() {
+        for name in ["summarize", "extract_wisdom", "my notes", "v1.2", "ünïcödé"] {
+            assert!(validate_name("Pattern", name).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_traversal_and_separators() {
+        for name in ["..", ".", "../../.env", "a/b", "a\\b", "/etc", ".env"] {
+            assert!(validate_name("Pattern", name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_reserved_windows_names() {
+        for name in ["CON", "con", "con.txt", "Nul.md", "LPT1", "com9.tar.gz"] {
+            assert!(validate_name("Context", name).is_err(), "{}", name);
+        }
+        assert!(validate_name("Context", "console").is_ok());
+    }
+
+    #[test]
+    fn rejects_trailing_dots_spaces_and_control_characters() {
+        for name in [
+            "name.", "name ", " name", "", "   ", "a\nb", "a\0b", "tab\t",
+        ] {
+            assert!(validate_name("Session", name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn limits_the_length_in_bytes() {
+        assert!(validate_name("Pattern", &"a".repeat(MAX_NAME_LEN)).is_ok());
+        assert!(validate_name("Pattern", &"a".repeat(MAX_NAME_LEN + 1)).is_err());
+        // 65 two-byte characters are fewer than 128 characters but more than 128 bytes
+        assert!(validate_name("Pattern", &"é".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn safe_join_rejects_anything_but_a_plain_file_name() {
+        let dir = tempfile::tempdir().unwrap();
+        for file_name in ["../x.md", "a/b.md", "..", "/etc/passwd"] {
+            assert!(safe_join(dir.path(), file_name).is_err(), "{}", file_name);
+        }
+        assert_eq!(
+            safe_join(dir.path(), "notes.md").unwrap(),
+            dir.path().join("notes.md")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn safe_join_rejects_symlinks_leading_outside() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("contexts");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(root.path().join(".env"), "OPENAI_API_KEY=sk").unwrap();
+        std::os::unix::fs::symlink(root.path().join(".env"), dir.join("leak.md")).unwrap();
+        std::os::unix::fs::symlink(root.path().join("missing"), dir.join("dangling.md")).unwrap();
+        fs::write(dir.join("real.md"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("real.md"), dir.join("alias.md")).unwrap();
+
+        assert!(safe_join(&dir, "leak.md").is_err());
+        assert!(safe_join(&dir, "dangling.md").is_err());
+        assert!(safe_join(&dir, "alias.md").is_ok());
+        assert!(safe_join(&dir, "new.md").is_ok());
+    }
+
+    #[test]
+    fn safe_join_allows_directories_that_do_not_exist_yet() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("not_created");
+        assert_eq!(safe_join(&dir, "a.md").unwrap(), dir.join("a.md"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::fabric::secrets::get_secret;
 use std::path::PathBuf;
 use tauri::Manager;
 
@@ -35,6 +36,46 @@ pub async fn get_fabric_config_dir(app: tauri::AppHandle) -> Result<PathBuf, Str
     Ok(config_dir)
 }
 
+/// Returns the path to the fabric patterns directory
+///
+/// ## Platform-specific
+///
+/// - **MacOS:** /Users/{user}/.config/fabric/patterns
+/// - **Windows:**
+#[tauri::command]
+pub async fn get_patterns_dir(app: tauri::AppHandle) -> Result<PathBuf, String> {
+    let mut patterns_dir = get_fabric_config_dir(app).await?;
+    patterns_dir.push("patterns");
+
+    Ok(patterns_dir)
+}
+
+/// Returns the path to the custom patterns directory
+///
+/// Uses `CUSTOM_PATTERNS_DIRECTORY` from the fabric .env file and falls back to
+/// `custom_patterns` inside the fabric config directory when it is not set.
+#[tauri::command]
+pub async fn get_custom_patterns_dir(app: tauri::AppHandle) -> Result<PathBuf, String> {
+    let configured = get_secret(app.clone(), "CUSTOM_PATTERNS_DIRECTORY".to_string())
+        .await
+        .unwrap_or_default();
+    let configured = configured.trim();
+
+    if configured.is_empty() {
+        let mut custom_dir = get_fabric_config_dir(app).await?;
+        custom_dir.push("custom_patterns");
+        return Ok(custom_dir);
+    }
+
+    // fabric expands a leading ~ to the home directory
+    if let Some(rest) = configured.strip_prefix("~/") {
+        let home = get_home_dir(app).await?;
+        return Ok(home.join(rest));
+    }
+
+    Ok(PathBuf::from(configured))
+}
+
 /// Returns the path to the fabric bin file
 ///
 /// ## Platform-specific
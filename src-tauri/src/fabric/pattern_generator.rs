@@ -0,0 +1,175 @@
+use crate::fabric::custom_patterns::save_custom_pattern;
+use crate::fabric::markdown::{purpose_line, split_sections, strip_code_fence, Section};
+use crate::fabric::names::validate_name;
+use crate::fabric::pattern_lint::Diagnostic;
+use crate::fabric::patterns::resolve_pattern_dir;
+use crate::fabric::run::{fabric_run_env, run_fabric_with_input, DEFAULT_FABRIC_BIN};
+use crate::state::AppState;
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+/// fabric's meta pattern that writes new patterns
+const CREATE_PATTERN: &str = "create_pattern";
+
+/// A generated pattern waiting for the user to review it
+#[derive(Serialize, Clone)]
+pub struct PatternDraft {
+    name: String,
+    description: String,
+    system: String,
+    sections: Vec<Section>,
+}
+
+/// Turns the Markdown produced by `create_pattern` into a draft
+fn parse_draft(name: &str, output: &str) -> Result<PatternDraft, String> {
+    let system = strip_code_fence(output).to_string();
+    let sections = split_sections(&system);
+
+    if !sections.iter().any(|s| !s.heading.is_empty()) {
+        return Err("fabric did not return a pattern with Markdown sections".to_string());
+    }
+
+    let description = purpose_line(&sections);
+
+    Ok(PatternDraft {
+        name: name.to_string(),
+        description,
+        system,
+        sections,
+    })
+}
+
+/// Generates a draft custom pattern from a natural-language description
+///
+/// The draft is kept in memory until it is confirmed or discarded.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `description` - What the new pattern should do
+/// * `name` - The name for the new pattern
+/// * `state` - The app state holding pending drafts
+///
+/// ### Returns
+///
+/// * `Result<PatternDraft, String>` - The parsed draft for review or error if generation fails
+#[tauri::command]
+pub async fn generate_pattern(
+    app: AppHandle,
+    description: String,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<PatternDraft, String> {
+    let name = name.trim().to_string();
+    if description.trim().is_empty() {
+        return Err("Describe what the pattern should do".to_string());
+    }
+    validate_name("Pattern", &name)?;
+    // Built-in and profile patterns would shadow or be shadowed by the new one
+    if resolve_pattern_dir(app.clone(), &name).await.is_ok() {
+        return Err(format!("A pattern named '{}' already exists", name));
+    }
+
+    let env = fabric_run_env(app, CREATE_PATTERN).await?;
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        run_fabric_with_input(
+            Path::new(DEFAULT_FABRIC_BIN),
+            &["--pattern", CREATE_PATTERN],
+            &description,
+            &env,
+        )
+    })
+    .await
+    .map_err(|e| format!("Pattern generation failed: {}", e))??;
+
+    let draft = parse_draft(&name, &output)?;
+
+    state
+        .pattern_drafts
+        .lock()
+        .map_err(|_| "Failed to lock pattern drafts".to_string())?
+        .insert(name, draft.clone());
+
+    Ok(draft)
+}
+
+/// Lists the generated drafts that haven't been confirmed or discarded
+///
+/// ### Arguments
+///
+/// * `state` - The app state holding pending drafts
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternDraft>, String>` - The pending drafts
+#[tauri::command]
+pub fn get_pattern_drafts(state: State<'_, AppState>) -> Result<Vec<PatternDraft>, String> {
+    let drafts = state
+        .pattern_drafts
+        .lock()
+        .map_err(|_| "Failed to lock pattern drafts".to_string())?;
+
+    Ok(drafts.values().cloned().collect())
+}
+
+/// Writes a reviewed draft to the custom patterns directory
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the draft
+/// * `system` - Optional edited system.md, defaults to the generated one
+/// * `state` - The app state holding pending drafts
+///
+/// ### Returns
+///
+/// * `Result<Vec<Diagnostic>, String>` - Lint warnings of the saved pattern, or error if the save was blocked
+#[tauri::command]
+pub async fn confirm_pattern_draft(
+    app: AppHandle,
+    name: String,
+    system: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Diagnostic>, String> {
+    let draft = state
+        .pattern_drafts
+        .lock()
+        .map_err(|_| "Failed to lock pattern drafts".to_string())?
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("No draft named '{}'", name))?;
+
+    let diagnostics =
+        save_custom_pattern(app, name.clone(), system.unwrap_or(draft.system), None).await?;
+
+    // Only drop the draft once it is safely on disk
+    state
+        .pattern_drafts
+        .lock()
+        .map_err(|_| "Failed to lock pattern drafts".to_string())?
+        .remove(&name);
+
+    Ok(diagnostics)
+}
+
+/// Throws away a generated draft without writing anything
+///
+/// ### Arguments
+///
+/// * `name` - The name of the draft
+/// * `state` - The app state holding pending drafts
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if operation fails
+#[tauri::command]
+pub fn discard_pattern_draft(name: String, state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .pattern_drafts
+        .lock()
+        .map_err(|_| "Failed to lock pattern drafts".to_string())?
+        .remove(&name);
+
+    Ok(())
+}
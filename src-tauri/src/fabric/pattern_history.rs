@@ -0,0 +1,266 @@
+use crate::fabric::diff::{diff_files, FileDiff};
+use crate::fabric::names::pattern_dir;
+use crate::fabric::paths::get_custom_patterns_dir;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+/// Versions live in a hidden folder inside the pattern directory, which fabric ignores
+const HISTORY_DIR: &str = ".history";
+
+#[derive(Serialize)]
+pub struct PatternVersion {
+    id: String,
+    created_at: u64,
+    files: Vec<String>,
+}
+
+/// Reads the top-level markdown files of a pattern or version directory
+fn read_version_files(dir: &Path) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    let mut files = BTreeMap::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path();
+        if name.starts_with('.') || !name.ends_with(".md") || !path.is_file() {
+            continue;
+        }
+        let contents =
+            fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        files.insert(name, contents);
+    }
+
+    Ok(files)
+}
+
+/// Returns the version ids of a pattern, oldest first
+fn version_ids(pattern_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(pattern_dir.join(HISTORY_DIR)) else {
+        return Vec::new();
+    };
+
+    let mut ids: Vec<u128> = entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    ids.sort();
+    ids.into_iter().map(|id| id.to_string()).collect()
+}
+
+/// Stores the current files of a pattern as a new version
+///
+/// Nothing is stored when the files are identical to the latest version.
+///
+/// ### Returns
+///
+/// * `Result<Option<String>, String>` - The id of the new version, if one was created
+pub fn record_pattern_version(pattern_dir: &Path) -> Result<Option<String>, String> {
+    let files = read_version_files(pattern_dir)?;
+    let history_dir = pattern_dir.join(HISTORY_DIR);
+
+    if let Some(latest) = version_ids(pattern_dir).last() {
+        if read_version_files(&history_dir.join(latest))? == files {
+            return Ok(None);
+        }
+    }
+
+    // Versions recorded within the same millisecond get consecutive ids
+    let mut stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    while history_dir.join(stamp.to_string()).exists() {
+        stamp += 1;
+    }
+    let id = stamp.to_string();
+    let version_dir = history_dir.join(&id);
+    fs::create_dir_all(&version_dir)
+        .map_err(|e| format!("Failed to create {}: {}", version_dir.display(), e))?;
+
+    for (name, contents) in files {
+        fs::write(version_dir.join(&name), contents)
+            .map_err(|e| format!("Failed to store version of {}: {}", name, e))?;
+    }
+
+    Ok(Some(id))
+}
+
+/// Resolves a custom pattern's directory, making sure it exists
+async fn get_custom_pattern_dir(app: AppHandle, name: &str) -> Result<PathBuf, String> {
+    let pattern_dir = pattern_dir(&get_custom_patterns_dir(app).await?, name)?;
+    if !pattern_dir.is_dir() {
+        return Err(format!("Custom pattern '{}' does not exist", name));
+    }
+
+    Ok(pattern_dir)
+}
+
+/// Resolves a version of a custom pattern, making sure it exists
+fn get_version_dir(pattern_dir: &Path, version: &str) -> Result<PathBuf, String> {
+    if version.is_empty() || !version.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid version id '{}'", version));
+    }
+
+    let version_dir = pattern_dir.join(HISTORY_DIR).join(version);
+    if !version_dir.is_dir() {
+        return Err(format!("Version '{}' does not exist", version));
+    }
+
+    Ok(version_dir)
+}
+
+/// Replaces the markdown files of a pattern with those of one of its versions
+///
+/// The current and the restored files are both recorded, so the restore can be undone.
+pub fn restore_version(pattern_dir: &Path, version: &str) -> Result<(), String> {
+    let files = read_version_files(&get_version_dir(pattern_dir, version)?)?;
+
+    // Keep the current files in case they were edited outside the app
+    record_pattern_version(pattern_dir)?;
+
+    // Drop markdown files that didn't exist in the restored version
+    for current in read_version_files(pattern_dir)?.into_keys() {
+        if !files.contains_key(&current) {
+            fs::remove_file(pattern_dir.join(&current))
+                .map_err(|e| format!("Failed to remove {}: {}", current, e))?;
+        }
+    }
+
+    for (file, contents) in &files {
+        fs::write(pattern_dir.join(file), contents)
+            .map_err(|e| format!("Failed to write {}: {}", file, e))?;
+    }
+
+    record_pattern_version(pattern_dir)?;
+    Ok(())
+}
+
+/// Lists the saved versions of a custom pattern, newest first
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the custom pattern
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternVersion>, String>` - The saved versions or error if operation fails
+#[tauri::command]
+pub async fn list_pattern_versions(
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<PatternVersion>, String> {
+    let pattern_dir = get_custom_pattern_dir(app, &name).await?;
+    let history_dir = pattern_dir.join(HISTORY_DIR);
+
+    let mut versions = Vec::new();
+    for id in version_ids(&pattern_dir).into_iter().rev() {
+        let files = read_version_files(&history_dir.join(&id))?;
+        versions.push(PatternVersion {
+            // Ids are millisecond timestamps
+            created_at: id.parse::<u64>().unwrap_or_default() / 1000,
+            files: files.into_keys().collect(),
+            id,
+        });
+    }
+
+    Ok(versions)
+}
+
+/// Shows the differences between two versions of a custom pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the custom pattern
+/// * `from` - The id of the older version
+/// * `to` - The id of the newer version
+///
+/// ### Returns
+///
+/// * `Result<Vec<FileDiff>, String>` - Unified diffs of every file that changed
+#[tauri::command]
+pub async fn diff_pattern_versions(
+    app: AppHandle,
+    name: String,
+    from: String,
+    to: String,
+) -> Result<Vec<FileDiff>, String> {
+    let pattern_dir = get_custom_pattern_dir(app, &name).await?;
+    let old = read_version_files(&get_version_dir(&pattern_dir, &from)?)?;
+    let new = read_version_files(&get_version_dir(&pattern_dir, &to)?)?;
+
+    Ok(diff_files(&old, &new, &from, &to))
+}
+
+/// Restores an earlier version of a custom pattern
+///
+/// The restored content is recorded as a new version, so the restore itself can be undone.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the custom pattern
+/// * `version` - The id of the version to restore
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - Success message on completion or error if operation fails
+#[tauri::command]
+pub async fn restore_pattern_version(
+    app: AppHandle,
+    name: String,
+    version: String,
+) -> Result<String, String> {
+    let pattern_dir = get_custom_pattern_dir(app, &name).await?;
+    restore_version(&pattern_dir, &version)?;
+
+    Ok(format!(
+        "Restored pattern '{}' to version {}",
+        name, version
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_before_and_after_a_save_are_both_kept() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("summarize");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("system.md"), "old").unwrap();
+
+        let before = record_pattern_version(&dir).unwrap().unwrap();
+        fs::write(dir.join("system.md"), "new").unwrap();
+        let after = record_pattern_version(&dir).unwrap().unwrap();
+
+        assert_ne!(before, after);
+        assert_eq!(version_ids(&dir), vec![before.clone(), after.clone()]);
+        let history_dir = dir.join(HISTORY_DIR);
+        assert_eq!(
+            fs::read_to_string(history_dir.join(before).join("system.md")).unwrap(),
+            "old"
+        );
+        assert_eq!(
+            fs::read_to_string(history_dir.join(after).join("system.md")).unwrap(),
+            "new"
+        );
+    }
+
+    #[test]
+    fn unchanged_files_are_not_recorded_twice() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("summarize");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("system.md"), "same").unwrap();
+
+        assert!(record_pattern_version(&dir).unwrap().is_some());
+        assert!(record_pattern_version(&dir).unwrap().is_none());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::fabric::paths::{get_custom_patterns_dir, get_patterns_dir};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize)]
+pub struct Diagnostic {
+    pub pattern: String,
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(pattern: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            pattern: pattern.to_string(),
+            severity,
+            file: None,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn at(mut self, file: &str, line: Option<usize>) -> Self {
+        self.file = Some(file.to_string());
+        self.line = line;
+        self
+    }
+}
+
+/// Returns true if any of the diagnostics should block a save
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+/// Describes the errors among the diagnostics as a single message
+pub fn describe_errors(pattern: &str, diagnostics: &[Diagnostic]) -> String {
+    let messages: Vec<String> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| match (&d.file, d.line) {
+            (Some(file), Some(line)) => format!("{}:{}: {}", file, line, d.message),
+            _ => d.message.clone(),
+        })
+        .collect();
+    format!("Pattern '{}' is invalid: {}", pattern, messages.join("; "))
+}
+
+/// Lints the markdown files of a single pattern
+///
+/// ### Arguments
+///
+/// * `pattern` - The name of the pattern
+/// * `files` - File names and contents of the pattern's top-level markdown files
+/// * `shadows_builtin` - Whether a built-in pattern with the same name exists
+pub fn lint_pattern_files(
+    pattern: &str,
+    files: &[(String, String)],
+    shadows_builtin: bool,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if !files.iter().any(|(name, _)| name == "system.md") {
+        diagnostics.push(
+            Diagnostic::new(pattern, Severity::Error, "Pattern has no system.md")
+                .at("system.md", None),
+        );
+    }
+
+    // fabric would silently run the custom pattern instead of the built-in one
+    if shadows_builtin {
+        diagnostics.push(Diagnostic::new(
+            pattern,
+            Severity::Error,
+            format!("Custom pattern shadows the built-in pattern '{}'", pattern),
+        ));
+    }
+
+    for (name, content) in files {
+        if content.trim().is_empty() {
+            // An empty system.md leaves fabric with no prompt at all
+            let severity = if name == "system.md" {
+                Severity::Error
+            } else {
+                Severity::Warning
+            };
+            diagnostics.push(
+                Diagnostic::new(pattern, severity, format!("{} is empty", name)).at(name, None),
+            );
+            continue;
+        }
+
+        diagnostics.extend(lint_placeholders(pattern, name, content));
+    }
+
+    diagnostics
+}
+
+/// Reports `{{` without a matching `}}` and vice versa
+fn lint_placeholders(pattern: &str, file: &str, content: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let mut rest = line;
+
+        loop {
+            let next_open = rest.find("{{");
+            let next_close = rest.find("}}");
+
+            match (next_open, next_close) {
+                (Some(o), c) if c.is_none_or(|c| o < c) => {
+                    open.push(line_number);
+                    rest = &rest[o + 2..];
+                }
+                (_, Some(c)) => {
+                    if open.pop().is_none() {
+                        diagnostics.push(
+                            Diagnostic::new(pattern, Severity::Error, "Unmatched `}}`")
+                                .at(file, Some(line_number)),
+                        );
+                    }
+                    rest = &rest[c + 2..];
+                }
+                _ => break,
+            }
+        }
+    }
+
+    for line_number in open {
+        diagnostics.push(
+            Diagnostic::new(pattern, Severity::Error, "Unclosed `{{`").at(file, Some(line_number)),
+        );
+    }
+
+    diagnostics
+}
+
+/// Reads the top-level markdown files of a pattern directory
+pub fn read_pattern_files(dir: &Path) -> Result<Vec<(String, String)>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    let mut files: Vec<(String, String)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if name.starts_with('.') || !name.ends_with(".md") || !entry.path().is_file() {
+                return None;
+            }
+
+            // Non UTF-8 files are linted as empty rather than aborting the whole run
+            let content = fs::read_to_string(entry.path()).unwrap_or_default();
+            Some((name, content))
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// Lists the pattern directories inside `dir`
+pub fn list_pattern_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            if !entry.file_type().ok()?.is_dir() {
+                return None;
+            }
+            entry
+                .file_name()
+                .into_string()
+                .ok()
+                .filter(|name| !name.starts_with('.'))
+        })
+        .collect();
+
+    names.sort();
+    names
+}
+
+/// Checks every built-in and custom pattern for common mistakes
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<Diagnostic>, String>` - All diagnostics found, or error if a directory can't be read
+#[tauri::command]
+pub async fn lint_patterns(app: AppHandle) -> Result<Vec<Diagnostic>, String> {
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
+    let custom_dir = get_custom_patterns_dir(app).await?;
+
+    let builtin_names = list_pattern_names(&patterns_dir);
+    let builtin_set: HashSet<&String> = builtin_names.iter().collect();
+
+    let mut diagnostics = Vec::new();
+
+    for name in &builtin_names {
+        let files = read_pattern_files(&patterns_dir.join(name))?;
+        diagnostics.extend(lint_pattern_files(name, &files, false));
+    }
+
+    // fabric's custom directory can point at the built-in one, don't report everything twice
+    if custom_dir != patterns_dir {
+        for name in list_pattern_names(&custom_dir) {
+            let files = read_pattern_files(&custom_dir.join(&name))?;
+            diagnostics.extend(lint_pattern_files(
+                &name,
+                &files,
+                builtin_set.contains(&name),
+            ));
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(system: &str) -> Vec<(String, String)> {
+        vec![("system.md".to_string(), system.to_string())]
+    }
+
+    #[test]
+    fn shadowing_a_builtin_blocks_the_save() {
+        let diagnostics = lint_pattern_files("summarize", &files("# IDENTITY\n"), true);
+        assert!(has_errors(&diagnostics));
+    }
+
+    #[test]
+    fn a_new_name_is_not_an_error() {
+        let diagnostics = lint_pattern_files("my_summary", &files("# IDENTITY\n"), false);
+        assert!(!has_errors(&diagnostics));
+    }
+
+    /// The severity, file and line of each diagnostic
+    fn found(diagnostics: &[Diagnostic]) -> Vec<(Severity, Option<&str>, Option<usize>)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.severity, d.file.as_deref(), d.line))
+            .collect()
+    }
+
+    #[test]
+    fn a_missing_system_md_is_an_error() {
+        let diagnostics = lint_pattern_files(
+            "my_summary",
+            &[("user.md".to_string(), "Summarize:".to_string())],
+            false,
+        );
+        assert_eq!(
+            found(&diagnostics),
+            vec![(Severity::Error, Some("system.md"), None)]
+        );
+    }
+
+    #[test]
+    fn an_empty_system_md_is_an_error_and_other_empty_files_a_warning() {
+        let diagnostics = lint_pattern_files(
+            "my_summary",
+            &[
+                ("system.md".to_string(), " \n\n".to_string()),
+                ("user.md".to_string(), String::new()),
+            ],
+            false,
+        );
+        assert_eq!(
+            found(&diagnostics),
+            vec![
+                (Severity::Error, Some("system.md"), None),
+                (Severity::Warning, Some("user.md"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unbalanced_placeholders_are_reported_on_their_line() {
+        let diagnostics = lint_pattern_files(
+            "my_summary",
+            &files("# IDENTITY\n{{input\n}} and {{lang}}\n}}\n{{ {{ nested }}\n"),
+            false,
+        );
+        assert_eq!(
+            found(&diagnostics),
+            vec![
+                (Severity::Error, Some("system.md"), Some(4)),
+                (Severity::Error, Some("system.md"), Some(5)),
+            ]
+        );
+
+        let balanced = lint_pattern_files("my_summary", &files("{{input}} {{lang}}\n"), false);
+        assert!(balanced.is_empty());
+    }
+}
@@ -0,0 +1,533 @@
+use crate::fabric::archive::{collect_files, read_zip, write_dir, write_zip};
+use crate::fabric::custom_patterns::ensure_custom_patterns_configured;
+use crate::fabric::names::{pattern_dir, validate_name};
+use crate::fabric::paths::{get_custom_patterns_dir, get_patterns_dir};
+use crate::fabric::pattern_history::record_pattern_version;
+use crate::fabric::pattern_lint::{describe_errors, has_errors, lint_pattern_files};
+use crate::fabric::patterns::resolve_pattern_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const PATTERNS_PREFIX: &str = "patterns/";
+
+#[derive(Serialize, Deserialize)]
+pub struct PackManifest {
+    name: String,
+    version: String,
+    author: String,
+    created_at: u64,
+    patterns: Vec<PackPattern>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PackPattern {
+    name: String,
+    checksum: String,
+    files: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct PackPatternPreview {
+    name: String,
+    files: Vec<String>,
+    checksum_valid: bool,
+    conflict: bool,
+}
+
+#[derive(Serialize)]
+pub struct PackPreview {
+    manifest: PackManifest,
+    patterns: Vec<PackPatternPreview>,
+}
+
+/// What to do with a pattern from a pack when importing it
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum ImportAction {
+    Install,
+    Rename { name: String },
+    Skip,
+    Overwrite,
+}
+
+#[derive(Serialize)]
+pub struct ImportResult {
+    name: String,
+    installed_as: Option<String>,
+    message: String,
+}
+
+/// Files of each pattern in a pack, keyed by pattern name and then by path inside the pattern
+type PackFiles = BTreeMap<String, BTreeMap<String, Vec<u8>>>;
+
+/// Computes a checksum over the paths and contents of a pattern's files
+fn pattern_checksum(files: &BTreeMap<String, Vec<u8>>) -> String {
+    let mut hasher = Sha256::new();
+    for (name, contents) in files {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(contents);
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Reads a pattern directory, leaving out hidden files such as edit history
+fn read_pattern_dir(dir: &Path) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let mut files = BTreeMap::new();
+    for (relative, path) in collect_files(dir)? {
+        if relative.split('/').any(|part| part.starts_with('.')) {
+            continue;
+        }
+        let contents =
+            fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        files.insert(relative, contents);
+    }
+
+    Ok(files)
+}
+
+/// Reads a pack archive into its manifest and the files of each pattern
+fn read_pack(archive: &Path) -> Result<(PackManifest, PackFiles), String> {
+    let entries = read_zip(archive)?;
+
+    let manifest = entries
+        .get(MANIFEST_FILE)
+        .ok_or_else(|| "Archive is not a pattern pack: manifest.json is missing".to_string())?;
+    let manifest: PackManifest = serde_json::from_slice(manifest)
+        .map_err(|e| format!("Failed to parse pack manifest: {}", e))?;
+
+    let mut patterns = PackFiles::new();
+    for (path, contents) in entries {
+        let Some((pattern, file)) = path
+            .strip_prefix(PATTERNS_PREFIX)
+            .and_then(|rest| rest.split_once('/'))
+        else {
+            continue;
+        };
+        patterns
+            .entry(pattern.to_string())
+            .or_default()
+            .insert(file.to_string(), contents);
+    }
+
+    Ok((manifest, patterns))
+}
+
+/// Checks that a file from a pack stays inside its pattern directory
+///
+/// Hidden files are rejected too, a pack must not replace the edit history.
+fn validate_pack_file_path(path: &str) -> Result<(), String> {
+    let is_valid = !path.contains('\\')
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && !part.starts_with('.'));
+    if !is_valid {
+        return Err(format!("Invalid file path '{}' in pack", path));
+    }
+
+    Ok(())
+}
+
+/// Returns true if a pattern with this name exists in either patterns directory
+fn pattern_exists(name: &str, patterns_dir: &Path, custom_dir: &Path) -> bool {
+    patterns_dir.join(name).is_dir() || custom_dir.join(name).is_dir()
+}
+
+/// Exports patterns into a single shareable archive with a manifest
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `names` - The names of the patterns to export
+/// * `dest` - Path of the archive to write
+/// * `pack_name` - Optional name of the pack, defaults to the archive's file name
+/// * `version` - Optional version of the pack, defaults to `1.0.0`
+/// * `author` - Optional author of the pack
+///
+/// ### Returns
+///
+/// * `Result<PackManifest, String>` - The manifest written into the archive or error if operation fails
+#[tauri::command]
+pub async fn export_patterns(
+    app: AppHandle,
+    names: Vec<String>,
+    dest: PathBuf,
+    pack_name: Option<String>,
+    version: Option<String>,
+    author: Option<String>,
+) -> Result<PackManifest, String> {
+    if names.is_empty() {
+        return Err("Select at least one pattern to export".to_string());
+    }
+
+    let mut entries = Vec::new();
+    let mut patterns = Vec::new();
+
+    for name in names {
+        let dir = resolve_pattern_dir(app.clone(), &name).await?;
+        let files = read_pattern_dir(&dir)?;
+
+        patterns.push(PackPattern {
+            name: name.clone(),
+            checksum: pattern_checksum(&files),
+            files: files.keys().cloned().collect(),
+        });
+        for (file, contents) in files {
+            entries.push((format!("{}{}/{}", PATTERNS_PREFIX, name, file), contents));
+        }
+    }
+
+    let default_name = dest
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "patterns".to_string());
+
+    let manifest = PackManifest {
+        name: pack_name.unwrap_or(default_name),
+        version: version.unwrap_or_else(|| "1.0.0".to_string()),
+        author: author.unwrap_or_default(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        patterns,
+    };
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize pack manifest: {}", e))?;
+    entries.insert(0, (MANIFEST_FILE.to_string(), manifest_json));
+
+    write_zip(&entries, &dest)?;
+    Ok(manifest)
+}
+
+/// Shows what a pattern pack contains without installing anything
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `archive` - Path of the pack archive
+///
+/// ### Returns
+///
+/// * `Result<PackPreview, String>` - The manifest plus per-pattern checksum and conflict information
+#[tauri::command]
+pub async fn preview_pattern_pack(app: AppHandle, archive: PathBuf) -> Result<PackPreview, String> {
+    let (manifest, files) = read_pack(&archive)?;
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
+    let custom_dir = get_custom_patterns_dir(app).await?;
+
+    let patterns = manifest
+        .patterns
+        .iter()
+        .map(|pattern| {
+            let pattern_files = files.get(&pattern.name);
+            PackPatternPreview {
+                name: pattern.name.clone(),
+                files: pattern_files
+                    .map(|f| f.keys().cloned().collect())
+                    .unwrap_or_default(),
+                checksum_valid: pattern_files
+                    .is_some_and(|f| pattern_checksum(f) == pattern.checksum),
+                conflict: pattern_exists(&pattern.name, &patterns_dir, &custom_dir),
+            }
+        })
+        .collect();
+
+    Ok(PackPreview { manifest, patterns })
+}
+
+/// Installs the patterns of a pack, see [`import_patterns`]
+fn install_pack(
+    manifest: &PackManifest,
+    files: &PackFiles,
+    mut actions: HashMap<String, ImportAction>,
+    patterns_dir: &Path,
+    custom_dir: &Path,
+) -> Result<Vec<ImportResult>, String> {
+    let mut results = Vec::new();
+    for pattern in &manifest.patterns {
+        let name = pattern.name.clone();
+        let skipped = |message: String| ImportResult {
+            name: name.clone(),
+            installed_as: None,
+            message,
+        };
+
+        if let Err(e) = validate_name("Pattern", &name) {
+            results.push(skipped(e));
+            continue;
+        }
+        let Some(pattern_files) = files.get(&pattern.name) else {
+            results.push(skipped(
+                "Pattern is listed in the manifest but missing".into(),
+            ));
+            continue;
+        };
+        if pattern_checksum(pattern_files) != pattern.checksum {
+            results.push(skipped(
+                "Checksum mismatch, pattern was not installed".into(),
+            ));
+            continue;
+        }
+
+        if let Err(e) = pattern_files
+            .keys()
+            .try_for_each(|file| validate_pack_file_path(file))
+        {
+            results.push(skipped(e));
+            continue;
+        }
+
+        let conflict = pattern_exists(&name, patterns_dir, custom_dir);
+        let target = match actions.remove(&name).unwrap_or(ImportAction::Install) {
+            ImportAction::Skip => {
+                results.push(skipped("Skipped".into()));
+                continue;
+            }
+            ImportAction::Install if conflict => {
+                results.push(skipped("A pattern with this name already exists".into()));
+                continue;
+            }
+            ImportAction::Install | ImportAction::Overwrite => name.clone(),
+            ImportAction::Rename { name: new_name } => {
+                if let Err(e) = validate_name("Pattern", &new_name) {
+                    results.push(skipped(e));
+                    continue;
+                }
+                if pattern_exists(&new_name, patterns_dir, custom_dir) {
+                    results.push(skipped(format!(
+                        "A pattern named '{}' already exists",
+                        new_name
+                    )));
+                    continue;
+                }
+                new_name
+            }
+        };
+
+        let target_dir = match pattern_dir(custom_dir, &target) {
+            Ok(dir) => dir,
+            Err(e) => {
+                results.push(skipped(e));
+                continue;
+            }
+        };
+
+        // Lint the pack files the same way a save from the editor would
+        let lint_files: Vec<(String, String)> = pattern_files
+            .iter()
+            .filter(|(file, _)| !file.contains('/') && file.ends_with(".md"))
+            .map(|(file, contents)| (file.clone(), String::from_utf8_lossy(contents).into_owned()))
+            .collect();
+        let shadows_builtin = custom_dir != patterns_dir && patterns_dir.join(&target).is_dir();
+        let diagnostics = lint_pattern_files(&target, &lint_files, shadows_builtin);
+        if has_errors(&diagnostics) {
+            results.push(skipped(describe_errors(&target, &diagnostics)));
+            continue;
+        }
+
+        // Keep the pattern being overwritten in its history so it can be restored
+        if target_dir.exists() {
+            record_pattern_version(&target_dir)?;
+        }
+        write_dir(&target_dir, pattern_files)?;
+        record_pattern_version(&target_dir)?;
+        results.push(ImportResult {
+            name,
+            installed_as: Some(target),
+            message: "Installed".to_string(),
+        });
+    }
+
+    Ok(results)
+}
+
+/// Installs the patterns of a pack into the custom patterns directory
+///
+/// Patterns without an entry in `actions` are installed when their name is free
+/// and skipped when it conflicts with an existing pattern.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `archive` - Path of the pack archive
+/// * `actions` - Optional install/rename/skip/overwrite choice per pattern name
+///
+/// ### Returns
+///
+/// * `Result<Vec<ImportResult>, String>` - What happened to each pattern in the pack
+#[tauri::command]
+pub async fn import_patterns(
+    app: AppHandle,
+    archive: PathBuf,
+    actions: Option<HashMap<String, ImportAction>>,
+) -> Result<Vec<ImportResult>, String> {
+    let (manifest, files) = read_pack(&archive)?;
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
+    let custom_dir = get_custom_patterns_dir(app.clone()).await?;
+
+    let results = install_pack(
+        &manifest,
+        &files,
+        actions.unwrap_or_default(),
+        &patterns_dir,
+        &custom_dir,
+    )?;
+
+    if results.iter().any(|r| r.installed_as.is_some()) {
+        ensure_custom_patterns_configured(app).await?;
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fabric::pattern_history::restore_version;
+
+    /// Builds a pack in memory with valid checksums
+    fn pack(patterns: &[(&str, &[(&str, &str)])]) -> (PackManifest, PackFiles) {
+        let mut files = PackFiles::new();
+        let mut manifest = PackManifest {
+            name: "test".to_string(),
+            version: "1.0.0".to_string(),
+            author: String::new(),
+            created_at: 0,
+            patterns: Vec::new(),
+        };
+        for (name, pattern_files) in patterns {
+            let pattern_files: BTreeMap<String, Vec<u8>> = pattern_files
+                .iter()
+                .map(|(file, contents)| (file.to_string(), contents.as_bytes().to_vec()))
+                .collect();
+            manifest.patterns.push(PackPattern {
+                name: name.to_string(),
+                checksum: pattern_checksum(&pattern_files),
+                files: pattern_files.keys().cloned().collect(),
+            });
+            files.insert(name.to_string(), pattern_files);
+        }
+        (manifest, files)
+    }
+
+    fn dirs(root: &Path) -> (PathBuf, PathBuf) {
+        let patterns_dir = root.join("fabric").join("patterns");
+        let custom_dir = root.join("fabric").join("custom");
+        fs::create_dir_all(&patterns_dir).unwrap();
+        fs::create_dir_all(&custom_dir).unwrap();
+        (patterns_dir, custom_dir)
+    }
+
+    #[test]
+    fn malicious_pack_writes_nothing_outside_the_custom_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let (patterns_dir, custom_dir) = dirs(root.path());
+        fs::write(root.path().join("fabric").join(".env"), "KEY=value\n").unwrap();
+
+        let (manifest, files) = pack(&[
+            ("..", &[("system.md", "# IDENTITY")]),
+            ("../../escape", &[("system.md", "# IDENTITY")]),
+            (".hidden", &[("system.md", "# IDENTITY")]),
+            ("nested", &[("../../../escape.md", "# IDENTITY")]),
+            ("history", &[(".history/1/system.md", "# IDENTITY")]),
+            ("renamed", &[("system.md", "# IDENTITY")]),
+        ]);
+        let actions = HashMap::from([
+            ("..".to_string(), ImportAction::Overwrite),
+            (
+                "renamed".to_string(),
+                ImportAction::Rename {
+                    name: "../../.env".to_string(),
+                },
+            ),
+        ]);
+
+        let results = install_pack(&manifest, &files, actions, &patterns_dir, &custom_dir).unwrap();
+
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(|r| r.installed_as.is_none()));
+        assert_eq!(
+            fs::read_to_string(root.path().join("fabric").join(".env")).unwrap(),
+            "KEY=value\n"
+        );
+        assert!(!root.path().join("escape").exists());
+        assert!(!root.path().join("escape.md").exists());
+        assert_eq!(fs::read_dir(&custom_dir).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(&patterns_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn valid_pack_is_installed() {
+        let root = tempfile::tempdir().unwrap();
+        let (patterns_dir, custom_dir) = dirs(root.path());
+
+        let (manifest, files) = pack(&[("summarize_v2", &[("system.md", "# IDENTITY")])]);
+        let results = install_pack(
+            &manifest,
+            &files,
+            HashMap::new(),
+            &patterns_dir,
+            &custom_dir,
+        )
+        .unwrap();
+
+        assert_eq!(results[0].installed_as.as_deref(), Some("summarize_v2"));
+        assert_eq!(
+            fs::read_to_string(custom_dir.join("summarize_v2").join("system.md")).unwrap(),
+            "# IDENTITY"
+        );
+    }
+
+    #[test]
+    fn overwrite_keeps_the_previous_pattern_restorable() {
+        let root = tempfile::tempdir().unwrap();
+        let (patterns_dir, custom_dir) = dirs(root.path());
+        let dir = custom_dir.join("summarize_v2");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("system.md"), "# OLD").unwrap();
+
+        let (manifest, files) = pack(&[("summarize_v2", &[("system.md", "# NEW")])]);
+        let actions = HashMap::from([("summarize_v2".to_string(), ImportAction::Overwrite)]);
+        let results = install_pack(&manifest, &files, actions, &patterns_dir, &custom_dir).unwrap();
+
+        assert_eq!(results[0].installed_as.as_deref(), Some("summarize_v2"));
+        assert_eq!(fs::read_to_string(dir.join("system.md")).unwrap(), "# NEW");
+
+        let mut versions: Vec<String> = fs::read_dir(dir.join(".history"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        versions.sort();
+        assert_eq!(versions.len(), 2);
+
+        restore_version(&dir, &versions[0]).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("system.md")).unwrap(), "# OLD");
+    }
+
+    #[test]
+    fn invalid_or_shadowing_patterns_are_not_installed() {
+        let root = tempfile::tempdir().unwrap();
+        let (patterns_dir, custom_dir) = dirs(root.path());
+        fs::create_dir_all(patterns_dir.join("summarize")).unwrap();
+
+        let (manifest, files) = pack(&[
+            ("summarize", &[("system.md", "# IDENTITY")]),
+            ("empty", &[("system.md", "")]),
+            ("broken", &[("system.md", "{{input")]),
+        ]);
+        let actions = HashMap::from([("summarize".to_string(), ImportAction::Overwrite)]);
+        let results = install_pack(&manifest, &files, actions, &patterns_dir, &custom_dir).unwrap();
+
+        assert!(results.iter().all(|r| r.installed_as.is_none()));
+        assert_eq!(fs::read_dir(&custom_dir).unwrap().count(), 0);
+    }
+}
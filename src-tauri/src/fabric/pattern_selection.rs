@@ -0,0 +1,130 @@
+use crate::fabric::patterns::resolve_pattern_dir;
+use crate::fabric::secrets::{get_secret, update_secret};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+const DEFAULT_PATTERN_KEY: &str = "DEFAULT_PATTERN";
+const SELECTED_PATTERN_KEY: &str = "SELECTED_PATTERN";
+
+/// Emitted with the new [`PatternSelection`] whenever the default or selected pattern changes
+pub const PATTERN_SELECTION_CHANGED: &str = "pattern-selection-changed";
+
+/// The persisted pattern selection, stored in the fabric .env file so it survives restarts
+#[derive(Serialize, Clone)]
+pub struct PatternSelection {
+    default_pattern: String,
+    selected_pattern: String,
+}
+
+impl PatternSelection {
+    /// The pattern runs should use: the selected pattern, falling back to the default
+    pub fn active(&self) -> Option<&str> {
+        [&self.selected_pattern, &self.default_pattern]
+            .into_iter()
+            .map(|p| p.trim())
+            .find(|p| !p.is_empty())
+    }
+}
+
+/// Loads the pattern selection from the .env file
+pub async fn load_pattern_selection(app: AppHandle) -> PatternSelection {
+    PatternSelection {
+        default_pattern: get_secret(app.clone(), DEFAULT_PATTERN_KEY.to_string())
+            .await
+            .unwrap_or_default(),
+        selected_pattern: get_secret(app, SELECTED_PATTERN_KEY.to_string())
+            .await
+            .unwrap_or_default(),
+    }
+}
+
+/// Resolves the pattern a run should use
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - The active pattern, or error if none is selected or it no longer exists
+pub async fn resolve_run_pattern(app: AppHandle) -> Result<String, String> {
+    let selection = load_pattern_selection(app.clone()).await;
+    let pattern = selection
+        .active()
+        .ok_or_else(|| "Please select a pattern first.".to_string())?
+        .to_string();
+
+    resolve_pattern_dir(app, &pattern).await?;
+    Ok(pattern)
+}
+
+/// Validates and stores one of the selection keys, then notifies the frontend
+async fn store_pattern(app: AppHandle, key: &str, pattern: String) -> Result<(), String> {
+    let pattern = pattern.trim().to_string();
+
+    // An empty pattern clears the selection
+    if !pattern.is_empty() {
+        resolve_pattern_dir(app.clone(), &pattern).await?;
+    }
+
+    update_secret(app.clone(), key.to_string(), pattern).await?;
+
+    let selection = load_pattern_selection(app.clone()).await;
+    app.emit(PATTERN_SELECTION_CHANGED, selection)
+        .map_err(|e| format!("Failed to emit pattern selection change: {}", e))
+}
+
+/// Sets the pattern used for the next runs
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `pattern` - The name of an existing pattern, or an empty string to fall back to the default
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if the pattern doesn't exist
+#[tauri::command]
+pub async fn set_selected_pattern(app: AppHandle, pattern: String) -> Result<(), String> {
+    store_pattern(app, SELECTED_PATTERN_KEY, pattern).await
+}
+
+/// Gets the pattern runs will use, falling back to the default pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - The active pattern, or an empty string if none is set
+#[tauri::command]
+pub async fn get_selected_pattern(app: AppHandle) -> Result<String, String> {
+    let selection = load_pattern_selection(app).await;
+    Ok(selection.active().unwrap_or_default().to_string())
+}
+
+/// Sets the default pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `pattern` - The name of an existing pattern, or an empty string to clear it
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if the pattern doesn't exist
+#[tauri::command]
+pub async fn set_default_pattern(app: AppHandle, pattern: String) -> Result<(), String> {
+    store_pattern(app, DEFAULT_PATTERN_KEY, pattern).await
+}
+
+/// Gets the default pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - The default pattern, or an empty string if none is set
+#[tauri::command]
+pub async fn get_default_pattern(app: AppHandle) -> Result<String, String> {
+    Ok(load_pattern_selection(app).await.default_pattern)
+}
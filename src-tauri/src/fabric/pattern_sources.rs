@@ -0,0 +1,257 @@
+use crate::fabric::dotenv::write_private_file;
+use crate::fabric::paths::{get_custom_patterns_dir, get_fabric_config_dir, get_patterns_dir};
+use crate::fabric::pattern_lint::list_pattern_names;
+use crate::fabric::profiles::active_overrides;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// Label of the source added for the active profile's patterns directory
+const PROFILE_SOURCE: &str = "profile";
+
+/// A labelled directory of patterns
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PatternSource {
+    pub label: String,
+    pub path: PathBuf,
+}
+
+/// A pattern together with the source it resolves to
+#[derive(Serialize)]
+pub struct PatternInfo {
+    pub name: String,
+    pub source: String,
+    pub path: PathBuf,
+    /// Labels of lower-precedence sources that also have a pattern with this name
+    shadows: Vec<String>,
+}
+
+/// Gets the file holding the ordered list of pattern sources
+async fn get_sources_path(app: AppHandle) -> Result<PathBuf, String> {
+    let mut path = get_fabric_config_dir(app).await?;
+    path.push("pattern_sources.json");
+
+    Ok(path)
+}
+
+/// Loads the pattern sources, highest precedence first
+///
+/// Without a saved list this mirrors fabric's own lookup: custom patterns
+/// shadow the official ones.
+pub async fn load_pattern_sources(app: AppHandle) -> Result<Vec<PatternSource>, String> {
+    let mut sources = Vec::new();
+
+    // The active profile's own patterns shadow every other source
+    if let Some(path) = active_overrides(app.clone()).await?.patterns_dir {
+        sources.push(PatternSource {
+            label: PROFILE_SOURCE.to_string(),
+            path,
+        });
+    }
+
+    let path = get_sources_path(app.clone()).await?;
+    if path.exists() {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read pattern sources: {}", e))?;
+        let saved: Vec<PatternSource> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse pattern sources: {}", e))?;
+        sources.extend(saved);
+        return Ok(sources);
+    }
+
+    let custom_dir = get_custom_patterns_dir(app.clone()).await?;
+    let patterns_dir = get_patterns_dir(app).await?;
+
+    sources.push(PatternSource {
+        label: "custom".to_string(),
+        path: custom_dir.clone(),
+    });
+    if custom_dir != patterns_dir {
+        sources.push(PatternSource {
+            label: "official".to_string(),
+            path: patterns_dir,
+        });
+    }
+
+    Ok(sources)
+}
+
+/// Resolves every pattern across the sources by precedence
+pub fn layer_patterns(sources: &[PatternSource]) -> Vec<PatternInfo> {
+    let mut patterns: BTreeMap<String, PatternInfo> = BTreeMap::new();
+
+    for source in sources {
+        for name in list_pattern_names(&source.path) {
+            match patterns.get_mut(&name) {
+                Some(existing) => existing.shadows.push(source.label.clone()),
+                None => {
+                    patterns.insert(
+                        name.clone(),
+                        PatternInfo {
+                            path: source.path.join(&name),
+                            name,
+                            source: source.label.clone(),
+                            shadows: Vec::new(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    patterns.into_values().collect()
+}
+
+/// Gets the ordered list of pattern sources
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternSource>, String>` - The sources, highest precedence first
+#[tauri::command]
+pub async fn get_pattern_sources(app: AppHandle) -> Result<Vec<PatternSource>, String> {
+    load_pattern_sources(app).await
+}
+
+/// Replaces the ordered list of pattern sources
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `sources` - The sources, highest precedence first
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if the list is invalid
+#[tauri::command]
+pub async fn set_pattern_sources(
+    app: AppHandle,
+    sources: Vec<PatternSource>,
+) -> Result<(), String> {
+    if sources.is_empty() {
+        return Err("At least one pattern source is required".to_string());
+    }
+
+    let mut labels = HashSet::new();
+    for source in &sources {
+        if source.label.trim().is_empty() {
+            return Err("Pattern sources need a label".to_string());
+        }
+        if source.label == PROFILE_SOURCE {
+            return Err(format!(
+                "'{}' is reserved for the active profile's patterns",
+                PROFILE_SOURCE
+            ));
+        }
+        if !labels.insert(source.label.as_str()) {
+            return Err(format!("Duplicate pattern source label '{}'", source.label));
+        }
+        if !source.path.is_absolute() {
+            return Err(format!(
+                "Pattern source '{}' must use an absolute path",
+                source.label
+            ));
+        }
+    }
+
+    let path = get_sources_path(app).await?;
+    let content = serde_json::to_string_pretty(&sources)
+        .map_err(|e| format!("Failed to serialize pattern sources: {}", e))?;
+    write_private_file(&path, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn source(root: &Path, label: &str, patterns: &[&str]) -> PatternSource {
+        let path = root.join(label);
+        for pattern in patterns {
+            fs::create_dir_all(path.join(pattern)).unwrap();
+        }
+        fs::create_dir_all(&path).unwrap();
+        PatternSource {
+            label: label.to_string(),
+            path,
+        }
+    }
+
+    /// Name, winning source and shadowed sources of each pattern
+    fn layered(sources: &[PatternSource]) -> Vec<(String, String, Vec<String>)> {
+        layer_patterns(sources)
+            .into_iter()
+            .map(|p| (p.name, p.source, p.shadows))
+            .collect()
+    }
+
+    fn entry(name: &str, source: &str, shadows: &[&str]) -> (String, String, Vec<String>) {
+        (
+            name.to_string(),
+            source.to_string(),
+            shadows.iter().map(|s| s.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn earlier_sources_shadow_later_ones() {
+        let root = tempfile::tempdir().unwrap();
+        let profile = source(root.path(), PROFILE_SOURCE, &["summarize"]);
+        let custom = source(root.path(), "custom", &["summarize", "my_summary"]);
+        let official = source(
+            root.path(),
+            "official",
+            &["summarize", "my_summary", "extract_wisdom", ".git"],
+        );
+        // Loose files are not patterns
+        fs::write(official.path.join("pattern_explanations.md"), "").unwrap();
+
+        assert_eq!(
+            layered(&[profile.clone(), custom.clone(), official.clone()]),
+            vec![
+                entry("extract_wisdom", "official", &[]),
+                entry("my_summary", "custom", &["official"]),
+                entry("summarize", PROFILE_SOURCE, &["custom", "official"]),
+            ]
+        );
+
+        let summarize = layer_patterns(&[profile, custom.clone(), official.clone()])
+            .into_iter()
+            .find(|p| p.name == "summarize")
+            .unwrap();
+        assert_eq!(
+            summarize.path,
+            root.path().join(PROFILE_SOURCE).join("summarize")
+        );
+
+        // The order of the sources decides, not their labels
+        assert_eq!(
+            layered(&[official, custom]),
+            vec![
+                entry("extract_wisdom", "official", &[]),
+                entry("my_summary", "official", &["custom"]),
+                entry("summarize", "official", &["custom"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_source_directories_are_skipped() {
+        let root = tempfile::tempdir().unwrap();
+        let missing = PatternSource {
+            label: "custom".to_string(),
+            path: root.path().join("missing"),
+        };
+        let official = source(root.path(), "official", &["summarize"]);
+
+        assert_eq!(
+            layered(&[missing, official]),
+            vec![entry("summarize", "official", &[])]
+        );
+    }
+}
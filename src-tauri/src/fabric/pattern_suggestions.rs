@@ -0,0 +1,322 @@
+use crate::fabric::markdown::{purpose_line, split_sections};
+use crate::fabric::pattern_sources::{layer_patterns, load_pattern_sources, PatternInfo};
+use crate::fabric::patterns::SYSTEM_FILE;
+use crate::fabric::run::{fabric_run_env, run_fabric_with_input, DEFAULT_FABRIC_BIN};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// fabric's meta pattern that recommends patterns for a task
+const SUGGEST_PATTERN: &str = "suggest_pattern";
+
+/// How many suggestions are returned when no limit is given
+const DEFAULT_LIMIT: usize = 5;
+
+const NAME_WEIGHT: f64 = 3.0;
+const DESCRIPTION_WEIGHT: f64 = 1.0;
+const FABRIC_WEIGHT: f64 = 5.0;
+
+/// Words too common to say anything about which pattern fits
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "also", "and", "are", "because", "been", "but", "can", "could", "for",
+    "from", "has", "have", "into", "its", "more", "not", "only", "our", "over", "should", "some",
+    "such", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this",
+    "was", "were", "what", "when", "which", "while", "who", "will", "with", "would", "you", "your",
+];
+
+/// A pattern recommended for an input
+#[derive(Serialize)]
+pub struct PatternSuggestion {
+    name: String,
+    source: String,
+    score: f64,
+    reasons: Vec<String>,
+}
+
+/// Splits text into lowercase words worth matching on
+fn keywords(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|word| word.to_lowercase())
+        .filter(|word| word.len() >= 3 && !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Whether two words are close enough to count as a match, e.g. "summarize" and "summary"
+fn words_match(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+
+    let shared = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    shared >= 5
+}
+
+/// Input keywords that match any of the pattern's words
+fn matching(input: &BTreeSet<String>, words: &BTreeSet<String>) -> Vec<String> {
+    input
+        .iter()
+        .filter(|word| words.iter().any(|other| words_match(word, other)))
+        .cloned()
+        .collect()
+}
+
+fn quote_all(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|w| format!("'{}'", w))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Scores a pattern by how well its name and description match the input
+fn score_pattern(input: &BTreeSet<String>, pattern: &PatternInfo) -> PatternSuggestion {
+    let name_words = keywords(&pattern.name);
+    let description = fs::read_to_string(pattern.path.join(SYSTEM_FILE))
+        .map(|system| purpose_line(&split_sections(&system)))
+        .unwrap_or_default();
+    let description_words = keywords(&description);
+
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+
+    let name_matches = matching(input, &name_words);
+    if !name_matches.is_empty() {
+        score += NAME_WEIGHT * name_matches.len() as f64;
+        reasons.push(format!("Name matches {}", quote_all(&name_matches)));
+    }
+
+    let description_matches: Vec<String> = matching(input, &description_words)
+        .into_iter()
+        .filter(|word| !name_matches.contains(word))
+        .collect();
+    if !description_matches.is_empty() {
+        score += DESCRIPTION_WEIGHT * description_matches.len() as f64;
+        reasons.push(format!(
+            "Description mentions {}",
+            quote_all(&description_matches)
+        ));
+    }
+
+    PatternSuggestion {
+        name: pattern.name.clone(),
+        source: pattern.source.clone(),
+        score,
+        reasons,
+    }
+}
+
+/// Picks the known pattern names out of `suggest_pattern`'s answer
+fn recommended_by_fabric(output: &str, known: &HashSet<&str>) -> HashSet<String> {
+    output
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .filter(|word| known.contains(word))
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Ranks the patterns that best fit an input
+///
+/// Patterns are scored locally by keyword matches against their names and
+/// descriptions. When `use_fabric` is set, fabric's `suggest_pattern` pattern is
+/// also asked and the patterns it names are boosted.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `input` - The text the user wants to process
+/// * `limit` - Optional number of suggestions, defaults to 5
+/// * `use_fabric` - Whether to also run fabric's `suggest_pattern`
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternSuggestion>, String>` - The best matches with the reasons they were picked
+#[tauri::command]
+pub async fn suggest_patterns(
+    app: AppHandle,
+    input: String,
+    limit: Option<usize>,
+    use_fabric: Option<bool>,
+) -> Result<Vec<PatternSuggestion>, String> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sources = load_pattern_sources(app.clone()).await?;
+    let patterns = layer_patterns(&sources);
+    let input_words = keywords(&input);
+
+    let mut recommended = HashSet::new();
+    let fabric_available = Path::new(DEFAULT_FABRIC_BIN).exists()
+        && patterns.iter().any(|p| p.name == SUGGEST_PATTERN);
+    if use_fabric.unwrap_or(false) && fabric_available {
+        let env = fabric_run_env(app, SUGGEST_PATTERN).await?;
+        let output = tauri::async_runtime::spawn_blocking(move || {
+            run_fabric_with_input(
+                Path::new(DEFAULT_FABRIC_BIN),
+                &["--pattern", SUGGEST_PATTERN],
+                &input,
+                &env,
+            )
+        })
+        .await
+        .map_err(|e| format!("Pattern suggestion failed: {}", e))?;
+
+        // The local ranking still stands on its own if fabric can't answer
+        match output {
+            Ok(output) => {
+                let known: HashSet<&str> = patterns.iter().map(|p| p.name.as_str()).collect();
+                recommended = recommended_by_fabric(&output, &known);
+            }
+            Err(e) => println!("suggest_pattern failed, using local ranking only: {}", e),
+        }
+    }
+
+    Ok(rank_patterns(
+        &input_words,
+        &patterns,
+        &recommended,
+        limit.unwrap_or(DEFAULT_LIMIT),
+    ))
+}
+
+/// Scores every pattern, boosts the ones fabric recommended and keeps the best `limit`
+///
+/// Patterns with the same score are ordered by name.
+fn rank_patterns(
+    input: &BTreeSet<String>,
+    patterns: &[PatternInfo],
+    recommended: &HashSet<String>,
+    limit: usize,
+) -> Vec<PatternSuggestion> {
+    let mut suggestions: Vec<PatternSuggestion> = patterns
+        .iter()
+        .map(|pattern| score_pattern(input, pattern))
+        .collect();
+
+    for suggestion in &mut suggestions {
+        if recommended.contains(&suggestion.name) {
+            suggestion.score += FABRIC_WEIGHT;
+            suggestion
+                .reasons
+                .push("Recommended by fabric's suggest_pattern".to_string());
+        }
+    }
+
+    suggestions.retain(|s| s.score > 0.0 && s.name != SUGGEST_PATTERN);
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.name.cmp(&b.name)));
+    suggestions.truncate(limit);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fabric::pattern_sources::PatternSource;
+
+    /// Patterns with a one line purpose each, as fabric's own patterns have
+    const PATTERNS: &[(&str, &str)] = &[
+        ("analyze_claims", "Analyze the truth of claims in the text."),
+        ("create_summary", "Create a short summary of text."),
+        (
+            "extract_wisdom",
+            "Extract insights and wisdom from content.",
+        ),
+        ("suggest_pattern", "Suggest patterns for a task."),
+        ("summarize", "Summarize content into key points."),
+        ("write_essay", "Write an essay about a topic."),
+    ];
+
+    /// An input, the patterns fabric recommended for it and the expected ranking
+    struct Case {
+        input: &'static str,
+        recommended: &'static [&'static str],
+        limit: usize,
+        expected: &'static [(&'static str, f64)],
+    }
+
+    #[test]
+    fn ranks_by_name_description_and_fabric_matches() {
+        let root = tempfile::tempdir().unwrap();
+        for (name, purpose) in PATTERNS {
+            let dir = root.path().join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(SYSTEM_FILE),
+                format!(
+                    "# IDENTITY and PURPOSE\n\n{}\n\n# STEPS\n\n- Read\n",
+                    purpose
+                ),
+            )
+            .unwrap();
+        }
+        let patterns = layer_patterns(&[PatternSource {
+            label: "official".to_string(),
+            path: root.path().to_path_buf(),
+        }]);
+
+        let cases = [
+            // Name matches weigh 3, close words match and ties go by name
+            Case {
+                input: "summarize this article",
+                recommended: &[],
+                limit: 5,
+                expected: &[("create_summary", 3.0), ("summarize", 3.0)],
+            },
+            // Description matches weigh 1 per word
+            Case {
+                input: "insights about content",
+                recommended: &[],
+                limit: 5,
+                expected: &[("extract_wisdom", 2.0), ("summarize", 1.0)],
+            },
+            // A word in both name and description only counts for the name
+            Case {
+                input: "extract",
+                recommended: &[],
+                limit: 5,
+                expected: &[("extract_wisdom", 3.0)],
+            },
+            // fabric's recommendation adds 5
+            Case {
+                input: "an essay",
+                recommended: &["summarize"],
+                limit: 5,
+                expected: &[("summarize", 5.0), ("write_essay", 3.0)],
+            },
+            Case {
+                input: "summarize this article",
+                recommended: &[],
+                limit: 1,
+                expected: &[("create_summary", 3.0)],
+            },
+            // suggest_pattern itself is never suggested
+            Case {
+                input: "suggest a pattern",
+                recommended: &["suggest_pattern"],
+                limit: 5,
+                expected: &[],
+            },
+            Case {
+                input: "quantum physics",
+                recommended: &[],
+                limit: 5,
+                expected: &[],
+            },
+        ];
+
+        for case in cases {
+            let recommended: HashSet<String> = case
+                .recommended
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+            let ranked = rank_patterns(&keywords(case.input), &patterns, &recommended, case.limit);
+            let ranked: Vec<(&str, f64)> =
+                ranked.iter().map(|s| (s.name.as_str(), s.score)).collect();
+
+            assert_eq!(ranked, case.expected, "{}", case.input);
+        }
+    }
+}
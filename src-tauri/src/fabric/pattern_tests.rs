@@ -0,0 +1,381 @@
+use crate::fabric::markdown::strip_code_fence;
+use crate::fabric::names::{safe_join, validate_name};
+use crate::fabric::paths::get_fabric_config_dir;
+use crate::fabric::patterns::{compose_user_message, resolve_pattern_dir, USER_FILE};
+use crate::fabric::run::{fabric_run_env, run_fabric_with_input, DEFAULT_FABRIC_BIN};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    /// The output contains a Markdown heading with this text, at any level
+    RequiredHeading { heading: String },
+    /// The output matches this regular expression
+    Regex { pattern: String },
+    /// The output is at most this many characters long
+    MaxLength { max: usize },
+    /// The output parses as JSON, optionally wrapped in a code fence
+    ValidJson,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PatternFixture {
+    name: String,
+    input: String,
+    assertions: Vec<Assertion>,
+}
+
+#[derive(Serialize)]
+pub struct AssertionResult {
+    assertion: Assertion,
+    passed: bool,
+    message: String,
+}
+
+#[derive(Serialize)]
+pub struct FixtureResult {
+    name: String,
+    passed: bool,
+    output: String,
+    error: Option<String>,
+    assertions: Vec<AssertionResult>,
+}
+
+#[derive(Serialize)]
+pub struct PatternTestReport {
+    pattern: String,
+    passed: bool,
+    fixtures: Vec<FixtureResult>,
+}
+
+/// Gets the file holding the fixtures of a pattern
+async fn get_fixtures_path(app: AppHandle, pattern: &str) -> Result<PathBuf, String> {
+    validate_name("Pattern", pattern)?;
+    let tests_dir = get_fabric_config_dir(app).await?.join("pattern_tests");
+
+    safe_join(&tests_dir, &format!("{}.json", pattern))
+}
+
+fn check_assertion(assertion: &Assertion, output: &str) -> AssertionResult {
+    let (passed, message) = match assertion {
+        Assertion::RequiredHeading { heading } => {
+            let wanted = heading.trim_start_matches('#').trim();
+            let found = output.lines().any(|line| {
+                let line = line.trim();
+                line.starts_with('#')
+                    && line
+                        .trim_start_matches('#')
+                        .trim()
+                        .eq_ignore_ascii_case(wanted)
+            });
+            if found {
+                (true, format!("Found heading '{}'", wanted))
+            } else {
+                (false, format!("Missing heading '{}'", wanted))
+            }
+        }
+        Assertion::Regex { pattern } => match Regex::new(pattern) {
+            Ok(regex) if regex.is_match(output) => (true, format!("Matched /{}/", pattern)),
+            Ok(_) => (false, format!("Output does not match /{}/", pattern)),
+            Err(e) => (false, format!("Invalid regex /{}/: {}", pattern, e)),
+        },
+        Assertion::MaxLength { max } => {
+            let length = output.chars().count();
+            if length <= *max {
+                (true, format!("{} characters (max {})", length, max))
+            } else {
+                (false, format!("{} characters exceeds max {}", length, max))
+            }
+        }
+        Assertion::ValidJson => {
+            match serde_json::from_str::<serde_json::Value>(strip_code_fence(output)) {
+                Ok(_) => (true, "Output is valid JSON".to_string()),
+                Err(e) => (false, format!("Output is not valid JSON: {}", e)),
+            }
+        }
+    };
+
+    AssertionResult {
+        assertion: assertion.clone(),
+        passed,
+        message,
+    }
+}
+
+/// Runs a single fixture and checks its assertions against the output
+fn run_fixture(
+    fabric_bin: &Path,
+    pattern: &str,
+    user: Option<&str>,
+    fixture: &PatternFixture,
+    env: &[(String, String)],
+) -> FixtureResult {
+    let stdin = compose_user_message(user, &fixture.input);
+    let output = match run_fabric_with_input(fabric_bin, &["--pattern", pattern], &stdin, env) {
+        Ok(output) => output,
+        Err(error) => {
+            return FixtureResult {
+                name: fixture.name.clone(),
+                passed: false,
+                output: String::new(),
+                error: Some(error),
+                assertions: Vec::new(),
+            }
+        }
+    };
+
+    let assertions: Vec<AssertionResult> = fixture
+        .assertions
+        .iter()
+        .map(|assertion| check_assertion(assertion, &output))
+        .collect();
+
+    FixtureResult {
+        name: fixture.name.clone(),
+        passed: assertions.iter().all(|a| a.passed),
+        output,
+        error: None,
+        assertions,
+    }
+}
+
+/// Runs every fixture of a pattern and checks its assertions
+///
+/// ### Arguments
+///
+/// * `fabric_bin` - The fabric binary to run, which can be a stub
+/// * `pattern` - The name of the pattern to test
+/// * `user` - The pattern's user.md, sent ahead of each input like a normal run does
+/// * `fixtures` - The inputs and assertions to run
+/// * `env` - Extra environment variables for the fabric process
+pub fn run_pattern_fixtures(
+    fabric_bin: &Path,
+    pattern: &str,
+    user: Option<&str>,
+    fixtures: &[PatternFixture],
+    env: &[(String, String)],
+) -> PatternTestReport {
+    let results: Vec<FixtureResult> = fixtures
+        .iter()
+        .map(|fixture| run_fixture(fabric_bin, pattern, user, fixture, env))
+        .collect();
+
+    PatternTestReport {
+        pattern: pattern.to_string(),
+        passed: results.iter().all(|r| r.passed),
+        fixtures: results,
+    }
+}
+
+/// Gets the test fixtures attached to a pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `pattern` - The name of the pattern
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternFixture>, String>` - The fixtures, empty if none were saved
+#[tauri::command]
+pub async fn get_pattern_fixtures(
+    app: AppHandle,
+    pattern: String,
+) -> Result<Vec<PatternFixture>, String> {
+    let path = get_fixtures_path(app, &pattern).await?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read fixtures: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse fixtures: {}", e))
+}
+
+/// Replaces the test fixtures attached to a pattern
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `pattern` - The name of the pattern
+/// * `fixtures` - The inputs and assertions to store
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if operation fails
+#[tauri::command]
+pub async fn save_pattern_fixtures(
+    app: AppHandle,
+    pattern: String,
+    fixtures: Vec<PatternFixture>,
+) -> Result<(), String> {
+    // Catch broken regexes when saving rather than on every run
+    for fixture in &fixtures {
+        for assertion in &fixture.assertions {
+            if let Assertion::Regex { pattern: regex } = assertion {
+                Regex::new(regex).map_err(|e| {
+                    format!("Fixture '{}' has an invalid regex: {}", fixture.name, e)
+                })?;
+            }
+        }
+    }
+
+    let path = get_fixtures_path(app, &pattern).await?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create fixtures directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(&fixtures)
+        .map_err(|e| format!("Failed to serialize fixtures: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write fixtures: {}", e))
+}
+
+/// Runs all fixtures of a pattern through fabric and reports pass/fail per assertion
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `pattern` - The name of the pattern to test
+///
+/// ### Returns
+///
+/// * `Result<PatternTestReport, String>` - Results per fixture, including the produced output
+#[tauri::command]
+pub async fn test_pattern(app: AppHandle, pattern: String) -> Result<PatternTestReport, String> {
+    let fixtures = get_pattern_fixtures(app.clone(), pattern.clone()).await?;
+    if fixtures.is_empty() {
+        return Err(format!("Pattern '{}' has no test fixtures", pattern));
+    }
+
+    let env = fabric_run_env(app.clone(), &pattern).await?;
+    let pattern_dir = resolve_pattern_dir(app, &pattern).await?;
+    let user = fs::read_to_string(pattern_dir.join(USER_FILE)).ok();
+
+    // fabric runs can take a while, keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        run_pattern_fixtures(
+            Path::new(DEFAULT_FABRIC_BIN),
+            &pattern,
+            user.as_deref(),
+            &fixtures,
+            &env,
+        )
+    })
+    .await
+    .map_err(|e| format!("Pattern test run failed: {}", e))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Writes a fake fabric that echoes its input back, or fails on `fail`
+    fn stub_fabric(dir: &Path) -> PathBuf {
+        let path = dir.join("fabric");
+        fs::write(
+            &path,
+            "#!/bin/sh\n[ \"$1\" = \"--pattern\" ] || exit 2\ninput=$(cat)\n\
+             [ \"$input\" = fail ] && { echo broken >&2; exit 1; }\nprintf '%s' \"$input\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn fixture(input: &str, assertions: Vec<Assertion>) -> PatternFixture {
+        PatternFixture {
+            name: input.lines().next().unwrap_or_default().to_string(),
+            input: input.to_string(),
+            assertions,
+        }
+    }
+
+    fn passed(report: &PatternTestReport) -> Vec<Vec<bool>> {
+        report
+            .fixtures
+            .iter()
+            .map(|f| f.assertions.iter().map(|a| a.passed).collect())
+            .collect()
+    }
+
+    #[test]
+    fn each_assertion_kind_passes_and_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let fabric = stub_fabric(dir.path());
+        let heading = || Assertion::RequiredHeading {
+            heading: "## Summary".to_string(),
+        };
+        let regex = || Assertion::Regex {
+            pattern: r"(?m)^- \w+".to_string(),
+        };
+
+        let fixtures = vec![
+            fixture(
+                "# summary\n- point",
+                vec![heading(), regex(), Assertion::MaxLength { max: 20 }],
+            ),
+            fixture(
+                "no heading here",
+                vec![heading(), regex(), Assertion::MaxLength { max: 5 }],
+            ),
+            fixture("```json\n{\"ok\": true}\n```", vec![Assertion::ValidJson]),
+            fixture("{not json", vec![Assertion::ValidJson]),
+        ];
+
+        let report = run_pattern_fixtures(&fabric, "summarize", None, &fixtures, &[]);
+
+        assert!(!report.passed);
+        assert_eq!(
+            passed(&report),
+            vec![
+                vec![true, true, true],
+                vec![false, false, false],
+                vec![true],
+                vec![false],
+            ]
+        );
+        assert_eq!(report.fixtures[0].output, "# summary\n- point");
+    }
+
+    #[test]
+    fn a_failing_run_is_reported_with_its_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let fabric = stub_fabric(dir.path());
+
+        let report = run_pattern_fixtures(
+            &fabric,
+            "summarize",
+            None,
+            &[fixture("fail", vec![Assertion::ValidJson])],
+            &[],
+        );
+
+        assert!(!report.passed);
+        assert!(report.fixtures[0]
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("broken")));
+    }
+
+    #[test]
+    fn user_md_is_sent_ahead_of_the_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let fabric = stub_fabric(dir.path());
+
+        let report = run_pattern_fixtures(
+            &fabric,
+            "summarize",
+            Some("Summarize this:"),
+            &[fixture("some text", vec![])],
+            &[],
+        );
+
+        assert_eq!(report.fixtures[0].output, "Summarize this:\n\nsome text");
+    }
+}
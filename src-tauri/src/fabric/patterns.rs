@@ -1,11 +1,53 @@
+use crate::fabric::names::pattern_dir;
+use crate::fabric::paths::get_patterns_dir;
+use crate::fabric::pattern_sources::{layer_patterns, load_pattern_sources, PatternInfo};
+use crate::fabric::profiles::profile_env;
+use crate::fabric::run::{fabric_run_env, run_fabric_with_input, DEFAULT_FABRIC_BIN};
 use crate::fabric::secrets::{get_secret, update_secret};
-use crate::state::AppState;
+use crate::fabric::snapshots::create_pattern_snapshot;
+use serde::Serialize;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use tauri::Error;
 use tauri::Manager;
-use tauri::{Error, State};
 use tauri_plugin_shell::ShellExt;
 
+pub const SYSTEM_FILE: &str = "system.md";
+pub const USER_FILE: &str = "user.md";
+
+/// The prompt files of a pattern
+#[derive(Serialize)]
+pub struct PatternContent {
+    pub system: String,
+    pub user: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PatternPreview {
+    pattern: String,
+    system: String,
+    /// The user message fabric receives: user.md followed by the input
+    user_message: String,
+}
+
+/// Reads the system.md and optional user.md of a pattern directory
+pub fn read_pattern_content(dir: &Path) -> Result<PatternContent, String> {
+    let system = fs::read_to_string(dir.join(SYSTEM_FILE))
+        .map_err(|e| format!("Failed to read {}: {}", SYSTEM_FILE, e))?;
+    let user = fs::read_to_string(dir.join(USER_FILE)).ok();
+
+    Ok(PatternContent { system, user })
+}
+
+/// Combines a pattern's user.md with the input the same way runs do
+pub fn compose_user_message(user: Option<&str>, input: &str) -> String {
+    match user.filter(|u| !u.trim().is_empty()) {
+        Some(user) => format!("{}\n\n{}", user, input),
+        None => input.to_string(),
+    }
+}
+
 #[tauri::command]
 pub async fn get_fabric_dir(app: tauri::AppHandle) -> Result<String, String> {
     // Get the config directory using the path resolver
@@ -23,71 +65,39 @@ pub async fn get_fabric_dir(app: tauri::AppHandle) -> Result<String, String> {
     Ok(patterns_dir)
 }
 
+/// Lists the patterns of every pattern source
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternInfo>, String>` - Each pattern with the source it resolves to and the sources it shadows
 #[tauri::command]
-pub async fn get_patterns(app: tauri::AppHandle) -> Result<Vec<String>, String> {
-    // Get the home directory and construct the path to .config/fabric/patterns
-    let mut patterns_dir = app
-        .path()
-        .home_dir()
-        .map_err(|_| "Could not find home directory".to_string())?;
-
-    // Append .config/fabric/patterns to the home directory
-    patterns_dir.push(".config");
-    patterns_dir.push("fabric");
-    patterns_dir.push("patterns");
+pub async fn get_patterns(app: tauri::AppHandle) -> Result<Vec<PatternInfo>, String> {
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
 
-    // Create directories if they don't exist
+    // Create the official patterns directory if it doesn't exist
     fs::create_dir_all(&patterns_dir)
         .map_err(|_| "Could not create patterns directory".to_string())?;
 
-    // Read the directory entries
-    let entries =
-        fs::read_dir(&patterns_dir).map_err(|_| "Could not read patterns directory".to_string())?;
-
-    // Filter for directories and collect their names
-    let folders: Vec<String> = entries
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            // Only include if it's a directory
-            if entry.file_type().ok()?.is_dir() {
-                // Convert the filename to a string
-                entry.file_name().into_string().ok()
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    Ok(folders)
+    let sources = load_pattern_sources(app).await?;
+    Ok(layer_patterns(&sources))
 }
 
-#[tauri::command]
-pub async fn set_selected_pattern(
-    pattern: String,
-    state: State<'_, AppState>,
-) -> Result<(), Error> {
-    // Lock the mutex to get mutable access
-    let mut selected_pattern = state
-        .selected_pattern
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)?;
-
-    // Set the new pattern
-    *selected_pattern = pattern;
-
-    Ok(())
-}
-
-#[tauri::command]
-pub async fn get_selected_pattern(state: State<'_, AppState>) -> Result<String, Error> {
-    // Lock the mutex to get access to the pattern
-    let selected_pattern = state
-        .selected_pattern
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)?;
+/// Finds the directory of a pattern by the precedence of the pattern sources
+///
+/// The first source that has a directory with this name wins.
+pub async fn resolve_pattern_dir(app: tauri::AppHandle, name: &str) -> Result<PathBuf, String> {
+    for source in load_pattern_sources(app).await? {
+        let dir = pattern_dir(&source.path, name)?;
+        if dir.is_dir() {
+            return Ok(dir);
+        }
+    }
 
-    // Clone the pattern to return it
-    Ok(selected_pattern.clone())
+    Err(format!("Pattern '{}' does not exist", name))
 }
 
 #[tauri::command]
@@ -123,10 +133,24 @@ pub async fn get_patterns_git_folder(app: tauri::AppHandle) -> Result<String, St
 pub async fn update_patterns(app: tauri::AppHandle) -> Result<String, Error> {
     println!("Starting pattern update...");
 
+    // Snapshot the current patterns so a bad upstream change can be rolled back
+    let snapshot = create_pattern_snapshot(app.clone())
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    if let Some(snapshot) = snapshot {
+        println!("Created pattern snapshot: {}", snapshot.id);
+    }
+
+    // The active profile may download from its own pattern repository
+    let env = profile_env(app.clone())
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+
     let shell = app.shell();
     let output = shell
         .command("/usr/local/bin/fabric")
         .args(["-U"])
+        .envs(env)
         .output()
         .await
         .map_err(|e| {
@@ -154,7 +178,10 @@ pub fn run_fabric(_app_handle: tauri::AppHandle, flag: String) -> Result<String,
         .output()
         .map_err(|e| {
             // Log the error if the command fails to start
-            println!("Failed to start fabric command for flag '{}'. Error: {}", flag, e);
+            println!(
+                "Failed to start fabric command for flag '{}'. Error: {}",
+                flag, e
+            );
             Error::FailedToReceiveMessage
         })?;
 
@@ -162,35 +189,89 @@ pub fn run_fabric(_app_handle: tauri::AppHandle, flag: String) -> Result<String,
         // Log the stderr to the console when the command fails
         let stderr_output = String::from_utf8_lossy(&output.stderr);
         let stdout_output = String::from_utf8_lossy(&output.stdout);
-        println!("Fabric command for flag '{}' failed. Status: {:?}. Stderr: {}. Stdout: {}", flag, output.status, stderr_output, stdout_output);
+        println!(
+            "Fabric command for flag '{}' failed. Status: {:?}. Stderr: {}. Stdout: {}",
+            flag, output.status, stderr_output, stdout_output
+        );
         return Err(Error::FailedToReceiveMessage);
     }
 
     Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
+/// Reads the system.md and user.md of any pattern, resolved by source precedence
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the pattern
+///
+/// ### Returns
+///
+/// * `Result<PatternContent, String>` - The prompt files or error if the pattern doesn't exist
 #[tauri::command]
-pub async fn set_default_pattern(pattern: String, state: State<'_, AppState>) -> Result<(), Error> {
-    // Lock the mutex to get mutable access
-    let mut default_pattern = state
-        .default_pattern
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)?;
+pub async fn read_pattern(app: tauri::AppHandle, name: String) -> Result<PatternContent, String> {
+    let dir = resolve_pattern_dir(app, &name).await?;
+    read_pattern_content(&dir)
+}
 
-    // Set the new default pattern
-    *default_pattern = pattern;
+/// Shows the prompts a run of a pattern would send for the given input
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the pattern
+/// * `input` - The input to combine with the pattern's user.md
+///
+/// ### Returns
+///
+/// * `Result<PatternPreview, String>` - The system prompt and the composed user message
+#[tauri::command]
+pub async fn preview_pattern(
+    app: tauri::AppHandle,
+    name: String,
+    input: String,
+) -> Result<PatternPreview, String> {
+    let dir = resolve_pattern_dir(app, &name).await?;
+    let content = read_pattern_content(&dir)?;
 
-    Ok(())
+    Ok(PatternPreview {
+        user_message: compose_user_message(content.user.as_deref(), &input),
+        system: content.system,
+        pattern: name,
+    })
 }
 
+/// Runs fabric's `--dry-run` for a pattern, with user.md combined with the input
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the pattern
+/// * `input` - The input to combine with the pattern's user.md
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - What fabric would send to the model, or error if the run fails
 #[tauri::command]
-pub async fn get_default_pattern(state: State<'_, AppState>) -> Result<String, Error> {
-    // Lock the mutex to get access to the pattern
-    let default_pattern = state
-        .default_pattern
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)?;
-
-    // Clone the pattern to return it
-    Ok(default_pattern.clone())
+pub async fn dry_run_pattern(
+    app: tauri::AppHandle,
+    name: String,
+    input: String,
+) -> Result<String, String> {
+    let dir = resolve_pattern_dir(app.clone(), &name).await?;
+    let content = read_pattern_content(&dir)?;
+    let message = compose_user_message(content.user.as_deref(), &input);
+    let env = fabric_run_env(app, &name).await?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        run_fabric_with_input(
+            Path::new(DEFAULT_FABRIC_BIN),
+            &["--dry-run", "--pattern", &name],
+            &message,
+            &env,
+        )
+    })
+    .await
+    .map_err(|e| format!("Dry run failed: {}", e))?
 }
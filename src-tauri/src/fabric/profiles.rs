@@ -0,0 +1,355 @@
+use crate::fabric::dotenv::{write_private_file, EnvFile};
+use crate::fabric::paths::get_fabric_config_dir;
+use crate::fabric::secret_store::{api_key_backend, is_api_key};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// The profile backed by fabric's own .env file, which always exists
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Emitted with the new profile name whenever the active profile changes
+pub const PROFILE_CHANGED: &str = "profile-changed";
+
+const PROFILES_DIR: &str = "profiles";
+const PROFILE_FILE: &str = "profile.json";
+const ENV_FILE: &str = ".env";
+
+/// Optional directories a profile uses instead of the shared ones
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ProfileOverrides {
+    /// Patterns that take precedence over every other pattern source
+    pub patterns_dir: Option<PathBuf>,
+    /// Contexts the app uses instead of the fabric contexts directory, which
+    /// fabric itself can't be pointed at
+    pub contexts_dir: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct Profile {
+    name: String,
+    is_active: bool,
+    #[serde(flatten)]
+    overrides: ProfileOverrides,
+}
+
+/// The persisted choice of profile
+#[derive(Serialize, Deserialize)]
+struct ProfileState {
+    active: String,
+}
+
+/// Gets the profile an env file belongs to, `None` for the default profile
+pub fn profile_of_env_path(env_path: &Path) -> Option<String> {
+    let profile_dir = env_path.parent()?;
+    if profile_dir.parent()?.file_name()? != PROFILES_DIR {
+        return None;
+    }
+
+    Some(profile_dir.file_name()?.to_string_lossy().into_owned())
+}
+
+async fn get_profiles_dir(app: AppHandle) -> Result<PathBuf, String> {
+    Ok(get_fabric_config_dir(app).await?.join(PROFILES_DIR))
+}
+
+async fn get_state_path(app: AppHandle) -> Result<PathBuf, String> {
+    Ok(get_fabric_config_dir(app).await?.join("profiles.json"))
+}
+
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Profile name '{}' may only contain letters, digits, '-' and '_'",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Gets the directory of a named profile, erroring if it doesn't exist
+async fn existing_profile_dir(app: AppHandle, name: &str) -> Result<PathBuf, String> {
+    validate_profile_name(name)?;
+    let dir = get_profiles_dir(app).await?.join(name);
+    if !dir.is_dir() {
+        return Err(format!("Profile '{}' does not exist", name));
+    }
+
+    Ok(dir)
+}
+
+/// Gets the name of the active profile, falling back to the default profile
+pub async fn active_profile(app: AppHandle) -> Result<String, String> {
+    let path = get_state_path(app.clone()).await?;
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(DEFAULT_PROFILE.to_string());
+    };
+    let state: ProfileState = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse profiles.json: {}", e))?;
+
+    // A profile deleted outside the app falls back to the default one
+    if state.active == DEFAULT_PROFILE || existing_profile_dir(app, &state.active).await.is_err() {
+        return Ok(DEFAULT_PROFILE.to_string());
+    }
+
+    Ok(state.active)
+}
+
+/// Gets the .env file of a profile
+pub async fn profile_env_path(app: AppHandle, name: &str) -> Result<PathBuf, String> {
+    if name == DEFAULT_PROFILE {
+        return Ok(get_fabric_config_dir(app).await?.join(ENV_FILE));
+    }
+
+    Ok(get_profiles_dir(app).await?.join(name).join(ENV_FILE))
+}
+
+fn read_overrides(profile_dir: &Path) -> Result<ProfileOverrides, String> {
+    match fs::read_to_string(profile_dir.join(PROFILE_FILE)) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", PROFILE_FILE, e)),
+        Err(_) => Ok(ProfileOverrides::default()),
+    }
+}
+
+fn write_overrides(profile_dir: &Path, overrides: &ProfileOverrides) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(overrides)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    write_private_file(&profile_dir.join(PROFILE_FILE), content.as_bytes())
+}
+
+/// Gets the directory overrides of the active profile
+pub async fn active_overrides(app: AppHandle) -> Result<ProfileOverrides, String> {
+    let name = active_profile(app.clone()).await?;
+    if name == DEFAULT_PROFILE {
+        return Ok(ProfileOverrides::default());
+    }
+
+    read_overrides(&get_profiles_dir(app).await?.join(name))
+}
+
+/// Builds the environment that makes fabric use the active profile
+///
+/// fabric always loads its own .env file but never overrides variables that
+/// are already set, so the profile's values are passed to the child process and
+/// keys that only the default profile sets are blanked out.
+pub async fn profile_env(app: AppHandle) -> Result<Vec<(String, String)>, String> {
+    let name = active_profile(app.clone()).await?;
+    if name == DEFAULT_PROFILE {
+        return Ok(Vec::new());
+    }
+
+    let default_env =
+        EnvFile::read(&profile_env_path(app.clone(), DEFAULT_PROFILE).await?).unwrap_or_default();
+    let env = EnvFile::read(&profile_env_path(app, &name).await?).unwrap_or_default();
+
+    let mut vars: Vec<(String, String)> = default_env
+        .entries()
+        .into_iter()
+        .filter(|(key, _)| env.get(key).is_none())
+        .map(|(key, _)| (key.to_string(), String::new()))
+        .collect();
+    vars.extend(
+        env.entries()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+    );
+
+    Ok(vars)
+}
+
+/// Lists the profiles, starting with the default one
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<Profile>, String>` - Every profile with its overrides and whether it is active
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Vec<Profile>, String> {
+    let active = active_profile(app.clone()).await?;
+    let mut profiles = vec![Profile {
+        is_active: active == DEFAULT_PROFILE,
+        name: DEFAULT_PROFILE.to_string(),
+        overrides: ProfileOverrides::default(),
+    }];
+
+    let profiles_dir = get_profiles_dir(app).await?;
+    let mut names: Vec<String> = fs::read_dir(&profiles_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    for name in names {
+        profiles.push(Profile {
+            is_active: active == name,
+            overrides: read_overrides(&profiles_dir.join(&name))?,
+            name,
+        });
+    }
+
+    Ok(profiles)
+}
+
+/// Creates an empty profile
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The name of the new profile
+/// * `patterns_dir` - Optional patterns directory that takes precedence for this profile
+/// * `contexts_dir` - Optional contexts directory used instead of the shared one
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if the name is taken or invalid
+#[tauri::command]
+pub async fn create_profile(
+    app: AppHandle,
+    name: String,
+    patterns_dir: Option<PathBuf>,
+    contexts_dir: Option<PathBuf>,
+) -> Result<(), String> {
+    let overrides = ProfileOverrides {
+        patterns_dir,
+        contexts_dir,
+    };
+    let profile_dir = new_profile_dir(app.clone(), &name).await?;
+
+    write_overrides(&profile_dir, &overrides)?;
+    EnvFile::default().write(&profile_dir.join(ENV_FILE))
+}
+
+/// Validates a new profile name and creates its directory
+async fn new_profile_dir(app: AppHandle, name: &str) -> Result<PathBuf, String> {
+    validate_profile_name(name)?;
+    if name == DEFAULT_PROFILE {
+        return Err(format!("'{}' is reserved", DEFAULT_PROFILE));
+    }
+
+    let profile_dir = get_profiles_dir(app).await?.join(name);
+    if profile_dir.exists() {
+        return Err(format!("Profile '{}' already exists", name));
+    }
+    fs::create_dir_all(&profile_dir).map_err(|e| format!("Failed to create profile: {}", e))?;
+
+    Ok(profile_dir)
+}
+
+/// Creates a profile with a copy of another profile's settings and keys
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `source` - The profile to copy
+/// * `name` - The name of the new profile
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if operation fails
+#[tauri::command]
+pub async fn clone_profile(app: AppHandle, source: String, name: String) -> Result<(), String> {
+    let overrides = if source == DEFAULT_PROFILE {
+        ProfileOverrides::default()
+    } else {
+        read_overrides(&existing_profile_dir(app.clone(), &source).await?)?
+    };
+
+    let source_env_path = profile_env_path(app.clone(), &source).await?;
+    let source_env = EnvFile::read(&source_env_path).unwrap_or_default();
+
+    let profile_dir = new_profile_dir(app, &name).await?;
+    let env_path = profile_dir.join(ENV_FILE);
+    write_overrides(&profile_dir, &overrides)?;
+    source_env.write(&env_path)?;
+
+    // Keys kept in the keyring are filed per profile, so copy them over too
+    let from = api_key_backend(&source_env_path);
+    let to = api_key_backend(&env_path);
+    for (key, _) in source_env.entries() {
+        if is_api_key(key) {
+            if let Some(value) = from.get(key)? {
+                to.set(key, &value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes a profile the active one for the app and for runs
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The profile to activate
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if the profile doesn't exist
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<(), String> {
+    if name != DEFAULT_PROFILE {
+        existing_profile_dir(app.clone(), &name).await?;
+    }
+
+    let state = serde_json::to_string_pretty(&ProfileState {
+        active: name.clone(),
+    })
+    .map_err(|e| format!("Failed to serialize profiles.json: {}", e))?;
+    write_private_file(&get_state_path(app.clone()).await?, state.as_bytes())?;
+
+    app.emit(PROFILE_CHANGED, name)
+        .map_err(|e| format!("Failed to emit profile change: {}", e))
+}
+
+/// Deletes a profile together with its keys
+///
+/// Deleting the active profile switches back to the default one.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `name` - The profile to delete
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if operation fails
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    if name == DEFAULT_PROFILE {
+        return Err("The default profile cannot be deleted".to_string());
+    }
+    let profile_dir = existing_profile_dir(app.clone(), &name).await?;
+
+    if active_profile(app.clone()).await? == name {
+        switch_profile(app, DEFAULT_PROFILE.to_string()).await?;
+    }
+
+    let env_path = profile_dir.join(ENV_FILE);
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+    let backend = api_key_backend(&env_path);
+    for (key, _) in env.entries() {
+        if is_api_key(key) {
+            backend.delete(key)?;
+        }
+    }
+
+    fs::remove_dir_all(&profile_dir).map_err(|e| format!("Failed to delete profile: {}", e))
+}
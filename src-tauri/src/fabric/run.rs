@@ -1,6 +1,132 @@
+use crate::fabric::paths::{get_patterns_dir, path_to_string};
+use crate::fabric::pattern_selection::resolve_run_pattern;
+use crate::fabric::patterns::{compose_user_message, resolve_pattern_dir, USER_FILE};
+use crate::fabric::profiles::profile_env;
+use crate::fabric::secret_store::api_key_env;
+use crate::plugins::get_clipboard_contents;
 use crate::state::AppState;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
 use tauri::{AppHandle, Error, State};
-use tauri_plugin_shell::ShellExt;
+
+pub const DEFAULT_FABRIC_BIN: &str = "/usr/local/bin/fabric";
+
+/// Runs fabric with `input` on stdin and returns its stdout
+///
+/// ### Arguments
+///
+/// * `fabric_bin` - The fabric binary to run, which can be a stub
+/// * `args` - Arguments for fabric, e.g. `["--pattern", "summarize"]`
+/// * `input` - Text written to fabric's stdin
+/// * `env` - Extra environment variables for the fabric process
+pub fn run_fabric_with_input(
+    fabric_bin: &Path,
+    args: &[&str],
+    input: &str,
+    env: &[(String, String)],
+) -> Result<String, String> {
+    let mut child = Command::new(fabric_bin)
+        .args(args)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", fabric_bin.display(), e))?;
+
+    // Write the input from another thread so a chatty child can't deadlock on a full pipe
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "Failed to open fabric stdin".to_string())?;
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run fabric: {}", e))?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(format!(
+            "fabric exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Builds the environment a fabric run of `pattern` needs
+///
+/// fabric only knows its official and custom pattern directories, so a pattern
+/// resolved from any other source is exposed to it as the custom directory.
+/// API keys kept outside the .env file are passed along as well.
+pub async fn fabric_run_env(
+    app: AppHandle,
+    pattern: &str,
+) -> Result<Vec<(String, String)>, String> {
+    // Later entries win, so keyring values replace the profile's placeholders
+    let mut env = profile_env(app.clone()).await?;
+    env.extend(api_key_env(app.clone()).await?);
+
+    let pattern_dir = resolve_pattern_dir(app.clone(), pattern).await?;
+    if let Some(source_dir) = pattern_dir.parent() {
+        if source_dir != get_patterns_dir(app).await? {
+            env.push((
+                "CUSTOM_PATTERNS_DIRECTORY".to_string(),
+                path_to_string(source_dir.to_path_buf())?,
+            ));
+        }
+    }
+
+    Ok(env)
+}
+
+/// fabric flags that turn a value into the input of a run
+const INPUT_FLAGS: &[&str] = &["-u", "-q"];
+
+/// Where the input of a pattern run comes from
+enum RunInput {
+    /// Text used as is, e.g. the clipboard
+    Text(String),
+    /// The output of `fabric <flag> <value>`, e.g. a scraped URL
+    Fabric { flag: String, value: String },
+}
+
+fn set_running(state: &State<'_, AppState>, running: bool) -> Result<(), Error> {
+    *state
+        .is_running
+        .lock()
+        .map_err(|_| Error::FailedToReceiveMessage)? = running;
+    Ok(())
+}
+
+/// Runs a pattern with its user.md ahead of the input, without going through a shell
+async fn run_pattern(app: AppHandle, pattern: String, input: RunInput) -> Result<String, String> {
+    let env = fabric_run_env(app.clone(), &pattern).await?;
+    let pattern_dir = resolve_pattern_dir(app, &pattern).await?;
+    let user = fs::read_to_string(pattern_dir.join(USER_FILE)).ok();
+
+    // fabric runs can take a while, keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let fabric_bin = Path::new(DEFAULT_FABRIC_BIN);
+        let input = match input {
+            RunInput::Text(text) => text,
+            RunInput::Fabric { flag, value } => {
+                run_fabric_with_input(fabric_bin, &[&flag, &value], "", &env)?
+            }
+        };
+        let stdin = compose_user_message(user.as_deref(), &input);
+
+        run_fabric_with_input(fabric_bin, &["--pattern", &pattern], &stdin, &env)
+    })
+    .await
+    .map_err(|e| format!("Pattern run failed: {}", e))?
+}
 
 // TODO get the jina ai functions to work from the rust side
 // TODO change function to "run_fabric_pattern"
@@ -12,59 +138,37 @@ pub async fn run_fabric_command(
     flag: String,
     state: State<'_, AppState>,
 ) -> Result<String, Error> {
-    // Set running state
-    *state
-        .is_running
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)? = true;
-
-    // Get selected pattern
-    let selected_pattern = state
-        .selected_pattern
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)?
-        .clone();
-
-    println!("Selected pattern: {}", selected_pattern);
-
-    // Check if pattern is None
-    if selected_pattern.is_empty() {
-        // Reset running state before returning error
-        *state
-            .is_running
-            .lock()
-            .map_err(|_| Error::FailedToReceiveMessage)? = false;
-
-        return Err(Error::FailedToReceiveMessage);
+    if !INPUT_FLAGS.contains(&flag.as_str()) {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Unsupported fabric flag '{}'", flag),
+        )));
     }
 
-    // Run command using shell plugin
-    let shell_command = format!(
-        " {} {} | fabric --pattern \"{}\"",
-        flag, input, selected_pattern
-    );
-    println!("Executing shell command: {}", shell_command);
+    set_running(&state, true)?;
 
-    let output = app
-        .shell()
-        .command("fabric")
-        .args(&[&shell_command])
-        .output()
-        .await
-        .map_err(|_| Error::FailedToReceiveMessage)?;
+    // Get the persisted pattern selection
+    let selected_pattern = match resolve_run_pattern(app.clone()).await {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            // Reset running state before returning error
+            set_running(&state, false)?;
+            return Err(Error::Io(std::io::Error::other(e)));
+        }
+    };
 
-    println!("Output: {}", String::from_utf8_lossy(&output.stdout));
+    println!("Selected pattern: {}", selected_pattern);
 
-    let result = String::from_utf8_lossy(&output.stdout).to_string();
-    println!("Command output: {}", result);
+    let result = run_pattern(
+        app,
+        selected_pattern,
+        RunInput::Fabric { flag, value: input },
+    )
+    .await;
 
-    // Reset running state
-    *state
-        .is_running
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)? = false;
+    set_running(&state, false)?;
 
-    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    result.map_err(|e| Error::Io(std::io::Error::other(e)))
 }
 
 #[tauri::command]
@@ -90,67 +194,26 @@ pub async fn clipboard_contents_and_run_pattern(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, Error> {
-    // Set running state
-    *state
-        .is_running
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)? = true;
-
-    // Get selected pattern
-    let selected_pattern = state
-        .selected_pattern
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)?
-        .clone();
-
-    // Check if pattern is None
-    if selected_pattern.is_empty() {
-        // Reset running state before returning error
-        *state
-            .is_running
-            .lock()
-            .map_err(|_| Error::FailedToReceiveMessage)? = false;
-        return Ok("Please select a pattern first.".to_string());
-    }
-
-    // Get platform-specific clipboard command
-    let clipboard_command = match tauri_plugin_os::platform() {
-        "windows" => "powershell.exe -command \"Get-Clipboard\"",
-        "macos" => "pbpaste",
-        "linux" => "xclip -selection clipboard -o",
-        _platform => {
-            *state
-                .is_running
-                .lock()
-                .map_err(|_| Error::FailedToReceiveMessage)? = false;
-            return Err(Error::FailedToReceiveMessage);
+    set_running(&state, true)?;
+
+    // Get the persisted pattern selection
+    let selected_pattern = match resolve_run_pattern(app.clone()).await {
+        Ok(pattern) => pattern,
+        Err(message) => {
+            // Reset running state before returning error
+            set_running(&state, false)?;
+            return Ok(message);
         }
     };
 
-    println!("Clipboard command: {}", clipboard_command);
-
-    // Run command using shell plugin
-    let shell_command = format!(
-        "{} | fabric --pattern \"{}\"",
-        clipboard_command, selected_pattern
-    );
-    println!("Executing shell command: {}", shell_command);
-
-    let output = app
-        .shell()
-        .command("sh")
-        .args(&["-c", &shell_command])
-        .output()
-        .await
-        .map_err(|_| Error::FailedToReceiveMessage)?;
+    let result = match get_clipboard_contents(app.clone()) {
+        Ok(contents) => run_pattern(app, selected_pattern, RunInput::Text(contents)).await,
+        Err(e) => Err(format!("Failed to read the clipboard: {}", e)),
+    };
 
-    // Reset running state
-    *state
-        .is_running
-        .lock()
-        .map_err(|_| Error::FailedToReceiveMessage)? = false;
+    set_running(&state, false)?;
 
-    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    result.map_err(|e| Error::Io(std::io::Error::other(e)))
 }
 
 // Get and set running state
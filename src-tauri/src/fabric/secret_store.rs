@@ -0,0 +1,515 @@
+use crate::fabric::dotenv::EnvFile;
+use crate::fabric::profiles::profile_of_env_path;
+use crate::fabric::secrets::get_env_file_path;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// .env key that records where API keys are stored
+pub const SECRET_BACKEND_KEY: &str = "SECRET_BACKEND";
+
+/// Service name the API keys are filed under in the OS keyring
+const KEYRING_SERVICE: &str = "fabric";
+
+/// Where `*_API_KEY` values are stored
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// In plaintext in the fabric .env file, where fabric itself looks for them
+    #[default]
+    File,
+    /// In the OS keyring (Secret Service, macOS Keychain, Windows Credential Manager)
+    Keyring,
+}
+
+impl BackendKind {
+    fn from_env(env: &EnvFile) -> Self {
+        match env.get(SECRET_BACKEND_KEY) {
+            Some("keyring") => BackendKind::Keyring,
+            _ => BackendKind::File,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackendKind::File => "file",
+            BackendKind::Keyring => "keyring",
+        }
+    }
+}
+
+/// Storage for secret values
+pub trait SecretBackend: Send + Sync {
+    /// Gets a secret, `None` if it isn't stored and `Some("")` once it was cleared
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+    /// Stores a secret, an empty value clears it
+    fn set(&self, key: &str, value: &str) -> Result<(), String>;
+    /// Removes a secret entirely
+    fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// Keeps secrets in the .env file
+pub struct FileBackend {
+    env_path: PathBuf,
+}
+
+impl FileBackend {
+    pub fn new(env_path: PathBuf) -> Self {
+        FileBackend { env_path }
+    }
+}
+
+impl SecretBackend for FileBackend {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let env = EnvFile::read(&self.env_path).unwrap_or_default();
+        Ok(env.get(key).map(String::from))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        EnvFile::update(&self.env_path, |env| {
+            env.set(key, value);
+            Ok(())
+        })
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        EnvFile::update(&self.env_path, |env| {
+            env.remove(key);
+            Ok(())
+        })
+    }
+}
+
+/// Raw entries in the OS keyring, without the .env placeholders
+struct OsKeyring {
+    service: String,
+}
+
+impl OsKeyring {
+    fn entry(&self, key: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(&self.service, key)
+            .map_err(|e| format!("Could not open keyring entry for {}: {}", key, e))
+    }
+}
+
+impl SecretBackend for OsKeyring {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        match self.entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Could not read {} from the keyring: {}", key, e)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.entry(key)?
+            .set_password(value)
+            .map_err(|e| format!("Could not store {} in the keyring: {}", key, e))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Could not remove {} from the keyring: {}", key, e)),
+        }
+    }
+}
+
+/// Keeps secrets in the OS keyring
+///
+/// The .env file keeps an empty `KEY=` line for every stored key, so the keys
+/// can still be listed without enumerating the keyring. A cleared key only
+/// keeps its placeholder and reads as an empty value. Each profile files its
+/// keys under its own service name.
+pub struct KeyringBackend {
+    env_path: PathBuf,
+    credentials: Box<dyn SecretBackend>,
+}
+
+impl KeyringBackend {
+    pub fn new(env_path: PathBuf) -> Self {
+        let service = match profile_of_env_path(&env_path) {
+            Some(profile) => format!("{}/{}", KEYRING_SERVICE, profile),
+            None => KEYRING_SERVICE.to_string(),
+        };
+
+        Self::with_credentials(env_path, Box::new(OsKeyring { service }))
+    }
+
+    /// Keeps the values in `credentials` instead of the OS keyring, e.g. a [`MockBackend`]
+    pub fn with_credentials(env_path: PathBuf, credentials: Box<dyn SecretBackend>) -> Self {
+        KeyringBackend {
+            env_path,
+            credentials,
+        }
+    }
+
+    /// Stores a value in the keyring without touching the .env file
+    pub fn set_credential(&self, key: &str, value: &str) -> Result<(), String> {
+        self.credentials.set(key, value)
+    }
+
+    /// Removes a value from the keyring without touching the .env file
+    pub fn delete_credential(&self, key: &str) -> Result<(), String> {
+        self.credentials.delete(key)
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        if let Some(value) = self.credentials.get(key)? {
+            return Ok(Some(value));
+        }
+
+        let env = EnvFile::read(&self.env_path).unwrap_or_default();
+        Ok(env.get(key).map(|_| String::new()))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            self.delete_credential(key)?;
+        } else {
+            self.set_credential(key, value)?;
+        }
+
+        // Keep the placeholder and make sure no plaintext copy is left behind
+        EnvFile::update(&self.env_path, |env| {
+            env.set(key, "");
+            Ok(())
+        })
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        self.delete_credential(key)?;
+        EnvFile::update(&self.env_path, |env| {
+            env.remove(key);
+            Ok(())
+        })
+    }
+}
+
+/// Keeps secrets in memory, for tests
+#[derive(Default)]
+pub struct MockBackend {
+    values: Mutex<HashMap<String, String>>,
+}
+
+impl SecretBackend for MockBackend {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let values = self.values.lock().map_err(|e| e.to_string())?;
+        Ok(values.get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        let mut values = self.values.lock().map_err(|e| e.to_string())?;
+        values.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        let mut values = self.values.lock().map_err(|e| e.to_string())?;
+        values.remove(key);
+        Ok(())
+    }
+}
+
+/// Whether a key holds an API key, which the secret backend is responsible for
+pub fn is_api_key(key: &str) -> bool {
+    key.ends_with("_API_KEY")
+}
+
+fn backend_for(kind: BackendKind, env_path: &Path) -> Box<dyn SecretBackend> {
+    match kind {
+        BackendKind::File => Box::new(FileBackend::new(env_path.to_path_buf())),
+        BackendKind::Keyring => Box::new(KeyringBackend::new(env_path.to_path_buf())),
+    }
+}
+
+/// Whether the API keys of an .env file are kept in the keyring
+pub fn uses_keyring(env: &EnvFile) -> bool {
+    BackendKind::from_env(env) == BackendKind::Keyring
+}
+
+/// Gets the backend that currently stores the API keys
+pub fn api_key_backend(env_path: &Path) -> Box<dyn SecretBackend> {
+    let env = EnvFile::read(env_path).unwrap_or_default();
+    backend_for(BackendKind::from_env(&env), env_path)
+}
+
+/// Collects the API keys that fabric can't read from the .env file itself
+///
+/// Keys kept in the keyring are handed to each fabric run through its
+/// environment, which takes precedence over the empty placeholders in .env.
+pub async fn api_key_env(app: AppHandle) -> Result<Vec<(String, String)>, String> {
+    let env_path = get_env_file_path(app).await?;
+    keyring_env(&env_path, &KeyringBackend::new(env_path.clone()))
+}
+
+/// Collects the non-empty API keys of an .env file that are kept in `keyring`
+fn keyring_env(
+    env_path: &Path,
+    keyring: &dyn SecretBackend,
+) -> Result<Vec<(String, String)>, String> {
+    let env = EnvFile::read(env_path).unwrap_or_default();
+    if !uses_keyring(&env) {
+        return Ok(Vec::new());
+    }
+
+    let mut vars = Vec::new();
+    for (key, _) in env.entries() {
+        if is_api_key(key) {
+            if let Some(value) = keyring.get(key)?.filter(|v| !v.is_empty()) {
+                vars.push((key.to_string(), value));
+            }
+        }
+    }
+
+    Ok(vars)
+}
+
+/// Gets where API keys are stored
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<BackendKind, String>` - `file` or `keyring`
+#[tauri::command]
+pub async fn get_secret_backend(app: AppHandle) -> Result<BackendKind, String> {
+    let env_path = get_env_file_path(app).await?;
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+
+    Ok(BackendKind::from_env(&env))
+}
+
+/// Switches where API keys are stored, moving the existing keys over
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `backend` - `file` or `keyring`
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success or error if a key couldn't be moved
+#[tauri::command]
+pub async fn set_secret_backend(app: AppHandle, backend: BackendKind) -> Result<(), String> {
+    let env_path = get_env_file_path(app).await?;
+    migrate_api_keys(&env_path, &KeyringBackend::new(env_path.clone()), backend)
+}
+
+/// Moves the API keys of an .env file between the file and `keyring`
+fn migrate_api_keys(
+    env_path: &Path,
+    keyring: &KeyringBackend,
+    backend: BackendKind,
+) -> Result<(), String> {
+    let env = EnvFile::read(env_path).unwrap_or_default();
+    let current = BackendKind::from_env(&env);
+    if current == backend {
+        return Ok(());
+    }
+
+    let keys: Vec<String> = env
+        .entries()
+        .into_iter()
+        .filter(|(key, _)| is_api_key(key))
+        .map(|(key, _)| key.to_string())
+        .collect();
+
+    // Copy the keys first and flip the .env file over in a single write, so a
+    // failure halfway leaves the old backend complete
+    match backend {
+        BackendKind::Keyring => {
+            for key in &keys {
+                let value = env.get(key).unwrap_or_default();
+                if !value.is_empty() {
+                    keyring.set_credential(key, value)?;
+                }
+            }
+
+            EnvFile::update(env_path, |env| {
+                for key in &keys {
+                    env.set(key, "");
+                }
+                env.set(SECRET_BACKEND_KEY, backend.as_str());
+                Ok(())
+            })
+        }
+        BackendKind::File => {
+            let mut values = Vec::new();
+            for key in &keys {
+                values.push((key, keyring.get(key)?.unwrap_or_default()));
+            }
+
+            EnvFile::update(env_path, |env| {
+                for (key, value) in &values {
+                    env.set(key, value);
+                }
+                env.set(SECRET_BACKEND_KEY, backend.as_str());
+                Ok(())
+            })?;
+
+            for key in &keys {
+                keyring.delete_credential(key)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// The behaviour every backend must share
+    fn check_contract(backend: &dyn SecretBackend) {
+        assert_eq!(backend.get("OPENAI_API_KEY").unwrap(), None);
+
+        backend.set("OPENAI_API_KEY", "sk-first").unwrap();
+        assert_eq!(
+            backend.get("OPENAI_API_KEY").unwrap().as_deref(),
+            Some("sk-first")
+        );
+        backend.set("OPENAI_API_KEY", "sk-second").unwrap();
+        assert_eq!(
+            backend.get("OPENAI_API_KEY").unwrap().as_deref(),
+            Some("sk-second")
+        );
+
+        // Clearing keeps the key around as an empty placeholder
+        backend.set("OPENAI_API_KEY", "").unwrap();
+        assert_eq!(backend.get("OPENAI_API_KEY").unwrap().as_deref(), Some(""));
+
+        backend.delete("OPENAI_API_KEY").unwrap();
+        assert_eq!(backend.get("OPENAI_API_KEY").unwrap(), None);
+        backend.delete("OPENAI_API_KEY").unwrap();
+    }
+
+    #[test]
+    fn mock_backend_follows_the_contract() {
+        check_contract(&MockBackend::default());
+    }
+
+    #[test]
+    fn file_backend_follows_the_contract() {
+        let dir = tempfile::tempdir().unwrap();
+        check_contract(&FileBackend::new(dir.path().join(".env")));
+    }
+
+    #[test]
+    fn file_backend_keeps_the_placeholder_and_other_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        fs::write(
+            &env_path,
+            "# keys\nDEFAULT_MODEL=gpt-4o\nOPENAI_API_KEY=sk-old\n",
+        )
+        .unwrap();
+        let backend = FileBackend::new(env_path.clone());
+
+        backend.set("OPENAI_API_KEY", "").unwrap();
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "# keys\nDEFAULT_MODEL=gpt-4o\nOPENAI_API_KEY=\n"
+        );
+
+        backend.delete("OPENAI_API_KEY").unwrap();
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "# keys\nDEFAULT_MODEL=gpt-4o\n"
+        );
+    }
+
+    fn mock_keyring(env_path: &Path) -> KeyringBackend {
+        KeyringBackend::with_credentials(env_path.to_path_buf(), Box::new(MockBackend::default()))
+    }
+
+    #[test]
+    fn keyring_backend_follows_the_contract() {
+        let dir = tempfile::tempdir().unwrap();
+        check_contract(&mock_keyring(&dir.path().join(".env")));
+    }
+
+    #[test]
+    fn keyring_backend_leaves_only_a_placeholder_in_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        fs::write(&env_path, "DEFAULT_MODEL=gpt-4o\n").unwrap();
+        let keyring = mock_keyring(&env_path);
+
+        keyring.set("OPENAI_API_KEY", "sk-secret").unwrap();
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "DEFAULT_MODEL=gpt-4o\nOPENAI_API_KEY=\n"
+        );
+        assert_eq!(
+            keyring.get("OPENAI_API_KEY").unwrap().as_deref(),
+            Some("sk-secret")
+        );
+    }
+
+    #[test]
+    fn only_keyring_api_keys_are_passed_to_fabric() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        fs::write(
+            &env_path,
+            "OPENAI_API_KEY=\nGROQ_API_KEY=\nDEFAULT_MODEL=gpt-4o\n",
+        )
+        .unwrap();
+        let keyring = mock_keyring(&env_path);
+        keyring
+            .set_credential("OPENAI_API_KEY", "sk-secret")
+            .unwrap();
+        keyring.set_credential("DEFAULT_MODEL", "ignored").unwrap();
+
+        // Keys in the file are read by fabric itself
+        assert_eq!(keyring_env(&env_path, &keyring).unwrap(), Vec::new());
+
+        fs::write(
+            &env_path,
+            "SECRET_BACKEND=keyring\nOPENAI_API_KEY=\nGROQ_API_KEY=\nDEFAULT_MODEL=gpt-4o\n",
+        )
+        .unwrap();
+        assert_eq!(
+            keyring_env(&env_path, &keyring).unwrap(),
+            vec![("OPENAI_API_KEY".to_string(), "sk-secret".to_string())]
+        );
+    }
+
+    #[test]
+    fn switching_backends_moves_the_api_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        fs::write(
+            &env_path,
+            "OPENAI_API_KEY=sk-secret\nGROQ_API_KEY=\nDEFAULT_MODEL=gpt-4o\n",
+        )
+        .unwrap();
+        let keyring = mock_keyring(&env_path);
+
+        migrate_api_keys(&env_path, &keyring, BackendKind::Keyring).unwrap();
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "OPENAI_API_KEY=\nGROQ_API_KEY=\nDEFAULT_MODEL=gpt-4o\nSECRET_BACKEND=keyring\n"
+        );
+        assert_eq!(
+            keyring.get("OPENAI_API_KEY").unwrap().as_deref(),
+            Some("sk-secret")
+        );
+
+        migrate_api_keys(&env_path, &keyring, BackendKind::File).unwrap();
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "OPENAI_API_KEY=sk-secret\nGROQ_API_KEY=\nDEFAULT_MODEL=gpt-4o\nSECRET_BACKEND=file\n"
+        );
+        // The credential is gone, only the file entry is left
+        assert_eq!(keyring.get("OPENAI_API_KEY").unwrap().as_deref(), Some(""));
+    }
+}
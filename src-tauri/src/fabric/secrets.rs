@@ -1,25 +1,69 @@
-use std::{fs, path::PathBuf};
-use tauri::Manager;
+use crate::fabric::dotenv::EnvFile;
+use crate::fabric::paths::get_fabric_config_dir;
+use crate::fabric::profiles::{active_profile, profile_env_path};
+use crate::fabric::secret_store::{
+    api_key_backend, is_api_key, uses_keyring, KeyringBackend, SecretBackend, SECRET_BACKEND_KEY,
+};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many trailing characters of a secret stay visible when it is masked
+const VISIBLE_CHARS: usize = 4;
+
+/// File in the fabric config directory that records every revealed secret
+const AUDIT_FILE: &str = "secret_audit.jsonl";
 
 #[derive(serde::Serialize)]
 pub struct Secret {
     name: String,
+    /// The value, masked for secrets so only its last characters reach the webview
     secret: String,
+    is_set: bool,
+}
+
+impl Secret {
+    fn plain(name: &str, value: String) -> Self {
+        Secret {
+            name: name.to_string(),
+            is_set: !value.is_empty(),
+            secret: value,
+        }
+    }
+
+    fn masked(name: &str, value: &str) -> Self {
+        Secret {
+            name: name.to_string(),
+            is_set: !value.is_empty(),
+            secret: mask_secret(value),
+        }
+    }
+}
+
+/// Masks a secret down to its last characters, e.g. `••••1a2b`
+///
+/// Values too short to hide anything are masked completely.
+pub fn mask_secret(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.is_empty() {
+        return String::new();
+    }
+    if chars.len() <= VISIBLE_CHARS * 2 {
+        return "••••".to_string();
+    }
+
+    let visible: String = chars[chars.len() - VISIBLE_CHARS..].iter().collect();
+    format!("••••{}", visible)
 }
 
 #[tauri::command]
 // TODO move this to path file
+/// Gets the .env file of the active profile
 pub async fn get_env_file_path(app: tauri::AppHandle) -> Result<PathBuf, String> {
-    let mut env_path: std::path::PathBuf = app
-        .path()
-        .home_dir()
-        .map_err(|_| "Could not find home directory".to_string())?;
-
-    env_path.push(".config");
-    env_path.push("fabric");
-    env_path.push(".env");
-
-    Ok(env_path)
+    let profile = active_profile(app.clone()).await?;
+    profile_env_path(app, &profile).await
 }
 
 #[tauri::command]
@@ -29,179 +73,341 @@ pub async fn update_secret(
     key: String,
     value: String,
 ) -> Result<(), String> {
+    reject_backend_key(&key)?;
     let env_path = get_env_file_path(app).await?;
 
-    // Read existing content or create empty string if file doesn't exist
-    let content = fs::read_to_string(&env_path).unwrap_or_default();
+    // API keys go wherever the secret backend keeps them
+    if is_api_key(&key) {
+        return api_key_backend(&env_path).set(&key, &value);
+    }
 
-    // Split content into lines
-    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    EnvFile::update(&env_path, |env| {
+        env.set(&key, &value);
+        Ok(())
+    })
+}
 
-    // Find if key exists
-    let key_prefix = format!("{}=", key);
-    if let Some(line) = lines.iter_mut().find(|line| line.starts_with(&key_prefix)) {
-        // Update existing key
-        *line = format!("{}={}", key, value);
-    } else {
-        // Create new key
-        lines.push(format!("{}={}", key, value));
+/// Gets the value of a key, reading API keys from the secret backend
+pub fn lookup(env: &EnvFile, env_path: &Path, key: &str) -> Result<Option<String>, String> {
+    if is_api_key(key) {
+        if let Some(value) = api_key_backend(env_path).get(key)? {
+            return Ok(Some(value));
+        }
     }
 
-    // Create directory if it doesn't exist
-    if let Some(parent) = env_path.parent() {
-        fs::create_dir_all(parent).map_err(|_| "Could not create config directory".to_string())?;
-    }
+    Ok(env.get(key).map(String::from))
+}
 
-    // Write back to file
-    let new_content = lines.join("\n") + "\n";
-    fs::write(&env_path, new_content).map_err(|_| "Could not write to .env file".to_string())?;
-    Ok(())
+/// Gets the unmasked value of a key for use on the Rust side, `None` if it isn't set
+pub async fn load_secret(app: tauri::AppHandle, key: &str) -> Result<Option<String>, String> {
+    let env_path = get_env_file_path(app).await?;
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+
+    lookup(&env, &env_path, key)
 }
 
 #[tauri::command]
 pub async fn get_secret(app: tauri::AppHandle, key: String) -> Result<String, String> {
     let env_path = get_env_file_path(app).await?;
+    let env = EnvFile::read(&env_path)?;
+
+    let value = lookup(&env, &env_path, &key)?
+        .ok_or_else(|| format!("Key '{}' not found in .env file", key))?;
 
-    // Read file content
-    let content =
-        fs::read_to_string(&env_path).map_err(|_| "Could not read .env file".to_string())?;
+    // API keys only leave the backend through reveal_secret
+    if is_api_key(&key) {
+        return Ok(mask_secret(&value));
+    }
 
-    // Find the line with the key
-    let key_prefix = format!("{}=", key);
-    let value = content
-        .lines()
-        .find(|line| line.starts_with(&key_prefix))
-        .map(|line| line[key_prefix.len()..].to_string())
+    Ok(value)
+}
+
+/// Gets the real value of a secret and records that it was revealed
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `key` - The name of the secret
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - The unmasked value or error if the key doesn't exist
+#[tauri::command]
+pub async fn reveal_secret(app: tauri::AppHandle, key: String) -> Result<String, String> {
+    let env_path = get_env_file_path(app.clone()).await?;
+    let env = EnvFile::read(&env_path)?;
+
+    let value = lookup(&env, &env_path, &key)?
         .ok_or_else(|| format!("Key '{}' not found in .env file", key))?;
 
+    record_audit(app, "reveal", &key).await?;
     Ok(value)
 }
 
+/// Appends an entry to the secret audit log
+async fn record_audit(app: tauri::AppHandle, action: &str, key: &str) -> Result<(), String> {
+    let mut path = get_fabric_config_dir(app).await?;
+    path.push(AUDIT_FILE);
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("System clock is before 1970: {}", e))?
+        .as_secs();
+    let entry = serde_json::json!({
+        "timestamp": timestamp,
+        "action": action,
+        "key": key,
+    });
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open secret audit log: {}", e))?;
+    writeln!(file, "{}", entry).map_err(|e| format!("Failed to write secret audit log: {}", e))
+}
+
 #[tauri::command]
 pub async fn get_secrets(app: tauri::AppHandle, keys: Vec<String>) -> Result<Vec<Secret>, String> {
     let env_path = get_env_file_path(app).await?;
-
-    // Read file content
-    let content =
-        fs::read_to_string(&env_path).map_err(|_| "Could not read .env file".to_string())?;
-
-    // Find all requested keys
-    let secrets: Vec<Secret> = keys
-        .into_iter()
-        .filter_map(|key| {
-            let key_prefix = format!("{}=", key);
-            content
-                .lines()
-                .find(|line| line.starts_with(&key_prefix))
-                .map(|line| {
-                    let secret = line[key_prefix.len()..].to_string();
-                    Secret {
-                        name: key.clone(),
-                        secret,
-                    }
-                })
-        })
-        .collect();
+    let env = EnvFile::read(&env_path)?;
+
+    // Find all requested keys, API keys only leave the backend through reveal_secret
+    let mut secrets = Vec::new();
+    for key in keys {
+        if let Some(secret) = lookup(&env, &env_path, &key)? {
+            secrets.push(if is_api_key(&key) {
+                Secret::masked(&key, &secret)
+            } else {
+                Secret::plain(&key, secret)
+            });
+        }
+    }
 
     Ok(secrets)
 }
 
 #[tauri::command]
 pub async fn reset_secret(app: tauri::AppHandle, key: String) -> Result<(), String> {
+    // Reset the key by setting an empty value, creating it if needed
+    update_secret(app, key, String::new()).await
+}
+
+/// Removes a key from the .env file, and from the keyring for API keys
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `key` - The name of the secret
+///
+/// ### Returns
+///
+/// * `Result<(), String>` - Empty result on success, also if the key didn't exist
+#[tauri::command]
+pub async fn delete_secret(app: tauri::AppHandle, key: String) -> Result<(), String> {
+    reject_backend_key(&key)?;
     let env_path = get_env_file_path(app).await?;
 
-    // Read existing content or create empty string if file doesn't exist
-    let content = fs::read_to_string(&env_path).unwrap_or_default();
+    if is_api_key(&key) {
+        return api_key_backend(&env_path).delete(&key);
+    }
 
-    // Split content into lines
-    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    EnvFile::update(&env_path, |env| {
+        env.remove(&key);
+        Ok(())
+    })
+}
 
-    // Find if key exists
-    let key_prefix = format!("{}=", key);
-    if let Some(line) = lines.iter_mut().find(|line| line.starts_with(&key_prefix)) {
-        // Reset the key by setting empty value
-        *line = format!("{}=", key);
-    } else {
-        // If key doesn't exist, create it with empty value
-        lines.push(format!("{}=", key));
+/// Rejects edits of the secret backend setting, which has to move the API keys along
+fn reject_backend_key(key: &str) -> Result<(), String> {
+    if key == SECRET_BACKEND_KEY {
+        return Err(format!(
+            "{} can only be changed with set_secret_backend",
+            SECRET_BACKEND_KEY
+        ));
     }
 
-    // Write back to file
-    let new_content = lines.join("\n") + "\n";
-    fs::write(&env_path, new_content).map_err(|_| "Could not write to .env file".to_string())?;
     Ok(())
 }
 
-#[tauri::command]
-pub async fn get_api_keys(app: tauri::AppHandle) -> Result<Vec<Secret>, String> {
-    let env_path = get_env_file_path(app).await?;
+fn validate_key(key: &str) -> Result<(), String> {
+    reject_backend_key(key)?;
 
-    // Read file content
-    let content = fs::read_to_string(&env_path).unwrap_or_default();
+    let valid = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("'{}' is not a valid .env key", key));
+    }
 
-    // Parse each line into a key-value pair and filter for API_KEY
-    let secrets: Vec<Secret> = content
-        .lines()
-        .filter(|line| !line.is_empty() && line.contains('='))
-        .map(|line| {
-            let mut parts = line.splitn(2, '=');
-            let name = parts.next().unwrap_or_default().to_string();
-            let secret = parts.next().unwrap_or_default().to_string();
+    Ok(())
+}
 
-            Secret { name, secret }
-        })
-        .filter(|secret| secret.name.contains("API_KEY"))
-        .collect();
+/// Restores the keyring values a failed bulk update had changed, best effort
+fn rollback_keyring(
+    keyring: &KeyringBackend,
+    keys: &[&String],
+    previous: &HashMap<String, Option<String>>,
+) {
+    for key in keys {
+        let _ = match previous.get(*key).cloned().flatten() {
+            Some(value) if !value.is_empty() => keyring.set_credential(key, &value),
+            _ => keyring.delete_credential(key),
+        };
+    }
+}
 
-    Ok(secrets)
+/// Applies `changes` to an .env file in a single write, see [`update_secrets`]
+///
+/// ### Returns
+///
+/// * `Result<HashMap<String, Option<String>>, String>` - The previous value of each changed key
+fn apply_secret_changes(
+    env_path: &Path,
+    keyring: &KeyringBackend,
+    changes: &HashMap<String, Option<String>>,
+) -> Result<HashMap<String, Option<String>>, String> {
+    let mut in_keyring = false;
+    let mut keyring_changes = Vec::new();
+    let mut previous = HashMap::new();
+
+    let result = EnvFile::update(env_path, |env| {
+        in_keyring = uses_keyring(env);
+
+        for key in changes.keys() {
+            let stored = if in_keyring && is_api_key(key) {
+                keyring.get(key)?
+            } else {
+                None
+            };
+            previous.insert(key.clone(), stored.or(env.get(key).map(String::from)));
+        }
+
+        if in_keyring {
+            for (key, value) in changes.iter().filter(|(key, _)| is_api_key(key)) {
+                match value.as_deref() {
+                    Some(value) if !value.is_empty() => keyring.set_credential(key, value)?,
+                    _ => keyring.delete_credential(key)?,
+                }
+                keyring_changes.push(key);
+            }
+        }
+
+        for (key, value) in changes {
+            match value {
+                // The keyring keeps an empty placeholder in the file
+                Some(_) if in_keyring && is_api_key(key) => env.set(key, ""),
+                Some(value) => env.set(key, value),
+                None => {
+                    env.remove(key);
+                }
+            }
+        }
+
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        if in_keyring {
+            rollback_keyring(keyring, &keyring_changes, &previous);
+        }
+        return Err(e);
+    }
+
+    Ok(previous)
 }
 
+/// Sets and deletes several keys in a single write of the .env file
+///
+/// Either every change is applied or none is. API keys kept in the keyring are
+/// changed there first and restored if anything fails. Changed API keys are
+/// recorded in the audit log, and their previous values are masked like in
+/// [`get_secret`], so undoing a change has to go through [`reveal_secret`].
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `changes` - New values by key, `null` deletes the key
+///
+/// ### Returns
+///
+/// * `Result<HashMap<String, Option<String>>, String>` - The previous value of each changed key, `null` if it didn't exist
 #[tauri::command]
-pub async fn get_base_urls(app: tauri::AppHandle) -> Result<Vec<Secret>, String> {
-    let env_path = get_env_file_path(app).await?;
+pub async fn update_secrets(
+    app: tauri::AppHandle,
+    changes: HashMap<String, Option<String>>,
+) -> Result<HashMap<String, Option<String>>, String> {
+    for key in changes.keys() {
+        validate_key(key)?;
+    }
 
-    // Read file content
-    let content = fs::read_to_string(&env_path).unwrap_or_default();
+    let env_path = get_env_file_path(app.clone()).await?;
+    let keyring = KeyringBackend::new(env_path.clone());
+    let previous = apply_secret_changes(&env_path, &keyring, &changes)?;
 
-    // Parse each line into a key-value pair and filter for API_KEY
-    let secrets: Vec<Secret> = content
-        .lines()
-        .filter(|line| !line.is_empty() && line.contains('='))
-        .map(|line| {
-            let mut parts = line.splitn(2, '=');
-            let name = parts.next().unwrap_or_default().to_string();
-            let secret = parts.next().unwrap_or_default().to_string();
+    // The changes are already written, a broken audit log must not report them as failed
+    for key in previous.keys().filter(|key| is_api_key(key)) {
+        if let Err(e) = record_audit(app.clone(), "bulk_update", key).await {
+            println!("Failed to record the update of {}: {}", key, e);
+        }
+    }
 
-            Secret { name, secret }
-        })
-        .filter(|secret| secret.name.contains("BASE_URL"))
-        .collect();
+    Ok(mask_api_keys(previous))
+}
 
-    Ok(secrets)
+/// Masks the values of the API keys among `values`
+fn mask_api_keys(values: HashMap<String, Option<String>>) -> HashMap<String, Option<String>> {
+    values
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Some(value) if is_api_key(&key) => Some(mask_secret(&value)),
+                value => value,
+            };
+            (key, value)
+        })
+        .collect()
 }
 
-#[tauri::command]
-pub async fn get_pattern_secrets(app: tauri::AppHandle) -> Result<Vec<Secret>, String> {
+/// Lists the .env entries whose name contains `pattern`, masking their values if asked
+async fn find_secrets(
+    app: tauri::AppHandle,
+    pattern: &str,
+    mask: bool,
+) -> Result<Vec<Secret>, String> {
     let env_path = get_env_file_path(app).await?;
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+
+    let mut secrets = Vec::new();
+    for (name, _) in env.entries() {
+        if name.contains(pattern) {
+            let value = lookup(&env, &env_path, name)?.unwrap_or_default();
+            secrets.push(if mask {
+                Secret::masked(name, &value)
+            } else {
+                Secret::plain(name, value)
+            });
+        }
+    }
 
-    // Read file content
-    let content = fs::read_to_string(&env_path).unwrap_or_default();
+    Ok(secrets)
+}
 
-    // Parse each line into a key-value pair and filter for API_KEY
-    let secrets: Vec<Secret> = content
-        .lines()
-        .filter(|line| !line.is_empty() && line.contains('='))
-        .map(|line| {
-            let mut parts = line.splitn(2, '=');
-            let name = parts.next().unwrap_or_default().to_string();
-            let secret = parts.next().unwrap_or_default().to_string();
+#[tauri::command]
+pub async fn get_api_keys(app: tauri::AppHandle) -> Result<Vec<Secret>, String> {
+    find_secrets(app, "API_KEY", true).await
+}
 
-            Secret { name, secret }
-        })
-        .filter(|secret| secret.name.contains("PATTERNS"))
-        .collect();
+#[tauri::command]
+pub async fn get_base_urls(app: tauri::AppHandle) -> Result<Vec<Secret>, String> {
+    find_secrets(app, "BASE_URL", false).await
+}
 
-    Ok(secrets)
+#[tauri::command]
+pub async fn get_pattern_secrets(app: tauri::AppHandle) -> Result<Vec<Secret>, String> {
+    find_secrets(app, "PATTERNS", false).await
 }
 
 // #[tauri::command]
@@ -251,3 +457,155 @@ pub async fn get_pattern_secrets(app: tauri::AppHandle) -> Result<Vec<Secret>, S
 
 //     Ok(secrets)
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fabric::secret_store::MockBackend;
+    use std::fs;
+
+    /// Credentials that refuse to store one key
+    struct FailingOn(&'static str, MockBackend);
+
+    impl SecretBackend for FailingOn {
+        fn get(&self, key: &str) -> Result<Option<String>, String> {
+            self.1.get(key)
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), String> {
+            if key == self.0 {
+                return Err(format!("Could not store {}", key));
+            }
+            self.1.set(key, value)
+        }
+
+        fn delete(&self, key: &str) -> Result<(), String> {
+            self.1.delete(key)
+        }
+    }
+
+    fn changes(pairs: &[(&str, Option<&str>)]) -> HashMap<String, Option<String>> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.map(String::from)))
+            .collect()
+    }
+
+    #[test]
+    fn validate_key_accepts_env_names() {
+        for key in ["OPENAI_API_KEY", "_PRIVATE", "model2"] {
+            assert!(validate_key(key).is_ok(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn validate_key_rejects_invalid_names_and_the_backend_setting() {
+        for key in ["", "1KEY", "A-B", "A B", "KEY\n", SECRET_BACKEND_KEY] {
+            assert!(validate_key(key).is_err(), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn bulk_update_keeps_api_keys_in_the_keyring() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        fs::write(
+            &env_path,
+            "SECRET_BACKEND=keyring\nOPENAI_API_KEY=\nDEFAULT_MODEL=gpt-4o\n",
+        )
+        .unwrap();
+        let keyring =
+            KeyringBackend::with_credentials(env_path.clone(), Box::new(MockBackend::default()));
+        keyring.set_credential("OPENAI_API_KEY", "sk-old").unwrap();
+
+        let previous = apply_secret_changes(
+            &env_path,
+            &keyring,
+            &changes(&[
+                ("OPENAI_API_KEY", Some("sk-new")),
+                ("DEFAULT_MODEL", Some("gpt-4.1")),
+                ("GROQ_API_KEY", None),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(previous["OPENAI_API_KEY"].as_deref(), Some("sk-old"));
+        assert_eq!(previous["DEFAULT_MODEL"].as_deref(), Some("gpt-4o"));
+        assert_eq!(previous["GROQ_API_KEY"], None);
+        assert_eq!(
+            keyring.get("OPENAI_API_KEY").unwrap().as_deref(),
+            Some("sk-new")
+        );
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "SECRET_BACKEND=keyring\nOPENAI_API_KEY=\nDEFAULT_MODEL=gpt-4.1\n"
+        );
+    }
+
+    #[test]
+    fn failed_bulk_update_rolls_the_keyring_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        let content =
+            "SECRET_BACKEND=keyring\nOPENAI_API_KEY=\nGROQ_API_KEY=\nDEFAULT_MODEL=gpt-4o\n";
+        fs::write(&env_path, content).unwrap();
+        let keyring = KeyringBackend::with_credentials(
+            env_path.clone(),
+            Box::new(FailingOn("GROQ_API_KEY", MockBackend::default())),
+        );
+        keyring.set_credential("OPENAI_API_KEY", "sk-old").unwrap();
+
+        let result = apply_secret_changes(
+            &env_path,
+            &keyring,
+            &changes(&[
+                ("OPENAI_API_KEY", Some("sk-new")),
+                ("GROQ_API_KEY", Some("gsk-new")),
+                ("DEFAULT_MODEL", Some("gpt-4.1")),
+            ]),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(
+            keyring.get("OPENAI_API_KEY").unwrap().as_deref(),
+            Some("sk-old")
+        );
+        assert_eq!(fs::read_to_string(&env_path).unwrap(), content);
+    }
+
+    #[test]
+    fn bulk_update_writes_api_keys_to_the_file_without_a_keyring() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        fs::write(&env_path, "OPENAI_API_KEY=sk-old\n").unwrap();
+        let keyring =
+            KeyringBackend::with_credentials(env_path.clone(), Box::new(MockBackend::default()));
+
+        apply_secret_changes(
+            &env_path,
+            &keyring,
+            &changes(&[("OPENAI_API_KEY", Some("sk-new"))]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&env_path).unwrap(),
+            "OPENAI_API_KEY=sk-new\n"
+        );
+        // Nothing was stored as a credential, only the file entry is left
+        assert_eq!(keyring.get("OPENAI_API_KEY").unwrap().as_deref(), Some(""));
+    }
+
+    #[test]
+    fn only_api_keys_are_masked() {
+        let masked = mask_api_keys(changes(&[
+            ("OPENAI_API_KEY", Some("sk-1234567890abcd")),
+            ("DEFAULT_MODEL", Some("gpt-4o")),
+            ("GROQ_API_KEY", None),
+        ]));
+
+        assert_eq!(masked["OPENAI_API_KEY"].as_deref(), Some("••••abcd"));
+        assert_eq!(masked["DEFAULT_MODEL"].as_deref(), Some("gpt-4o"));
+        assert_eq!(masked["GROQ_API_KEY"], None);
+    }
+}
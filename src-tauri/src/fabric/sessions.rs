@@ -1,8 +1,16 @@
+use crate::fabric::names::validate_name;
 use crate::fabric::patterns::run_fabric;
 use tauri::{AppHandle, Error};
 
+/// fabric stores each session as `sessions/<name>.json`, so names must be safe file names
+fn check_session_name(session: &str) -> Result<(), Error> {
+    validate_name("Session", session)
+        .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)))
+}
+
 #[tauri::command]
 pub async fn set_session(app: AppHandle, session: String) -> Result<String, Error> {
+    check_session_name(&session)?;
     run_fabric(app, format!("--session={}", session))
 }
 
@@ -18,10 +26,12 @@ pub async fn output_session(app: AppHandle) -> Result<String, Error> {
 
 #[tauri::command]
 pub async fn wipe_session(app: AppHandle, session: String) -> Result<String, Error> {
+    check_session_name(&session)?;
     run_fabric(app, format!("--wipesession={}", session))
 }
 
 #[tauri::command]
 pub async fn print_session(app: AppHandle, session: String) -> Result<String, Error> {
+    check_session_name(&session)?;
     run_fabric(app, format!("--printsession={}", session))
 }
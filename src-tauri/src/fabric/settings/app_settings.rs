@@ -0,0 +1,382 @@
+use crate::fabric::custom_patterns::ensure_custom_patterns_configured;
+use crate::fabric::dotenv::EnvFile;
+use crate::fabric::names::validate_name;
+use crate::fabric::secrets::get_env_file_path;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The kind of value a setting holds
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettingKind {
+    Text,
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// A git repository URL
+    Url,
+    /// A name that is used as a file name, empty when unset
+    Name {
+        label: &'static str,
+    },
+    /// An absolute directory or one relative to the home directory with `~/`, empty when unset
+    Directory,
+}
+
+/// Describes one of the known .env settings
+#[derive(Serialize)]
+pub struct SettingSchema {
+    key: &'static str,
+    #[serde(flatten)]
+    kind: SettingKind,
+    default: &'static str,
+    description: &'static str,
+}
+
+/// Every setting the app understands; other .env keys are left untouched
+pub const SETTINGS_SCHEMA: &[SettingSchema] = &[
+    SettingSchema {
+        key: "DEFAULT_VENDOR",
+        kind: SettingKind::Text,
+        default: "",
+        description: "The vendor fabric uses when no model is given",
+    },
+    SettingSchema {
+        key: "DEFAULT_MODEL",
+        kind: SettingKind::Text,
+        default: "",
+        description: "The model fabric uses when none is given",
+    },
+    SettingSchema {
+        key: "CURRENT_CONTEXT",
+        kind: SettingKind::Name { label: "Context" },
+        default: "",
+        description: "The context added to every run",
+    },
+    SettingSchema {
+        key: "TEMPERATURE",
+        kind: SettingKind::Number { min: 0.0, max: 2.0 },
+        default: "0.7",
+        description: "Randomness of the output, higher is more creative",
+    },
+    SettingSchema {
+        key: "TOP_P",
+        kind: SettingKind::Number { min: 0.0, max: 1.0 },
+        default: "1",
+        description: "Only sample from the most likely tokens that add up to this probability",
+    },
+    SettingSchema {
+        key: "PRESENCE_PENALTY",
+        kind: SettingKind::Number {
+            min: -2.0,
+            max: 2.0,
+        },
+        default: "0",
+        description: "Penalises tokens that already appeared, encouraging new topics",
+    },
+    SettingSchema {
+        key: "FREQUENCY_PENALTY",
+        kind: SettingKind::Number {
+            min: -2.0,
+            max: 2.0,
+        },
+        default: "0",
+        description: "Penalises tokens by how often they appeared, reducing repetition",
+    },
+    SettingSchema {
+        key: "PATTERNS_LOADER_GIT_REPO_URL",
+        kind: SettingKind::Url,
+        default: "https://github.com/danielmiessler/fabric.git",
+        description: "The git repository patterns are downloaded from",
+    },
+    SettingSchema {
+        key: "PATTERNS_LOADER_GIT_REPO_PATTERNS_FOLDER",
+        kind: SettingKind::Text,
+        default: "patterns",
+        description: "The folder of the repository that holds the patterns",
+    },
+    SettingSchema {
+        key: "CUSTOM_PATTERNS_DIRECTORY",
+        kind: SettingKind::Directory,
+        default: "",
+        description: "Where your own patterns are stored",
+    },
+];
+
+/// The known settings with their typed values
+#[derive(Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Settings {
+    default_vendor: String,
+    default_model: String,
+    current_context: String,
+    temperature: f64,
+    top_p: f64,
+    presence_penalty: f64,
+    frequency_penalty: f64,
+    patterns_loader_git_repo_url: String,
+    patterns_loader_git_repo_patterns_folder: String,
+    custom_patterns_directory: String,
+}
+
+fn schema(key: &str) -> Option<&'static SettingSchema> {
+    SETTINGS_SCHEMA.iter().find(|s| s.key == key)
+}
+
+/// Checks a value against a setting's schema
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - The value as it is written to .env, or why it is invalid
+fn validate(schema: &SettingSchema, value: &Value) -> Result<String, String> {
+    let text = match value {
+        Value::String(text) => text.trim().to_string(),
+        Value::Number(number) => number.to_string(),
+        _ => return Err(format!("{} must be a string or number", schema.key)),
+    };
+
+    if text.contains(['\n', '\r']) {
+        return Err(format!("{} must be a single line", schema.key));
+    }
+
+    match schema.kind {
+        SettingKind::Text => Ok(text),
+        SettingKind::Name { .. } | SettingKind::Directory if text.is_empty() => Ok(text),
+        SettingKind::Name { label } => {
+            validate_name(label, &text)?;
+            Ok(text)
+        }
+        SettingKind::Directory => {
+            // fabric expands a leading ~ to the home directory
+            if !Path::new(&text).is_absolute() && !text.starts_with("~/") {
+                return Err(format!(
+                    "{} must be an absolute path or start with ~/",
+                    schema.key
+                ));
+            }
+            Ok(text)
+        }
+        SettingKind::Number { min, max } => {
+            let number: f64 = text
+                .parse()
+                .map_err(|_| format!("{} must be a number", schema.key))?;
+            if !number.is_finite() || number < min || number > max {
+                return Err(format!(
+                    "{} must be between {} and {}",
+                    schema.key, min, max
+                ));
+            }
+            Ok(number.to_string())
+        }
+        SettingKind::Url => {
+            let valid = ["https://", "http://", "git@", "ssh://", "file://"]
+                .iter()
+                .any(|scheme| text.starts_with(scheme));
+            if !valid || text.contains(char::is_whitespace) {
+                return Err(format!("{} must be a git repository URL", schema.key));
+            }
+            Ok(text)
+        }
+    }
+}
+
+/// Reads the known settings, falling back to the default for missing or invalid values
+fn load_settings(env: &EnvFile) -> Settings {
+    let value = |key: &str| match schema(key) {
+        Some(schema) => env
+            .get(key)
+            .filter(|v| !v.is_empty())
+            .and_then(|v| validate(schema, &Value::String(v.to_string())).ok())
+            .unwrap_or_else(|| schema.default.to_string()),
+        None => String::new(),
+    };
+    let number = |key: &str| value(key).parse().unwrap_or_default();
+
+    Settings {
+        default_vendor: value("DEFAULT_VENDOR"),
+        default_model: value("DEFAULT_MODEL"),
+        current_context: value("CURRENT_CONTEXT"),
+        temperature: number("TEMPERATURE"),
+        top_p: number("TOP_P"),
+        presence_penalty: number("PRESENCE_PENALTY"),
+        frequency_penalty: number("FREQUENCY_PENALTY"),
+        patterns_loader_git_repo_url: value("PATTERNS_LOADER_GIT_REPO_URL"),
+        patterns_loader_git_repo_patterns_folder: value("PATTERNS_LOADER_GIT_REPO_PATTERNS_FOLDER"),
+        custom_patterns_directory: value("CUSTOM_PATTERNS_DIRECTORY"),
+    }
+}
+
+/// Gets the type, range, default and description of every known setting
+///
+/// ### Returns
+///
+/// * `&'static [SettingSchema]` - The schema of each setting
+#[tauri::command]
+pub fn get_settings_schema() -> &'static [SettingSchema] {
+    SETTINGS_SCHEMA
+}
+
+/// Gets the known settings from the .env file
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Settings, String>` - The typed settings with defaults filled in
+#[tauri::command]
+pub async fn get_settings(app: tauri::AppHandle) -> Result<Settings, String> {
+    let env_path = get_env_file_path(app).await?;
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+
+    Ok(load_settings(&env))
+}
+
+/// Validates and stores settings in a single write
+///
+/// Nothing is written if any value is invalid. Keys that aren't part of the
+/// schema are rejected here and left untouched in the file. Clearing
+/// `CUSTOM_PATTERNS_DIRECTORY` resets it to the default directory.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `changes` - The settings to change, by .env key
+///
+/// ### Returns
+///
+/// * `Result<Settings, String>` - The settings after the update, or error naming the invalid value
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    changes: HashMap<String, Value>,
+) -> Result<Settings, String> {
+    let mut values = Vec::new();
+    for (key, value) in &changes {
+        let schema = schema(key).ok_or_else(|| format!("Unknown setting '{}'", key))?;
+        values.push((schema.key, validate(schema, value)?));
+    }
+
+    let env_path = get_env_file_path(app.clone()).await?;
+    let settings = EnvFile::update(&env_path, |env| {
+        for (key, value) in &values {
+            env.set(key, value);
+        }
+        Ok(load_settings(env))
+    })?;
+
+    // fabric only finds the custom patterns through this setting, so clearing it
+    // points it back at the default directory
+    if changes.contains_key("CUSTOM_PATTERNS_DIRECTORY")
+        && settings.custom_patterns_directory.is_empty()
+    {
+        ensure_custom_patterns_configured(app.clone()).await?;
+        return get_settings(app).await;
+    }
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check(key: &str, value: Value) -> Result<String, String> {
+        validate(schema(key).unwrap(), &value)
+    }
+
+    #[test]
+    fn numbers_must_be_within_their_range() {
+        assert_eq!(check("TEMPERATURE", json!(0.5)).as_deref(), Ok("0.5"));
+        assert_eq!(check("TEMPERATURE", json!(" 2 ")).as_deref(), Ok("2"));
+        assert_eq!(check("PRESENCE_PENALTY", json!(-2)).as_deref(), Ok("-2"));
+
+        for value in [
+            json!(2.5),
+            json!(-0.1),
+            json!("NaN"),
+            json!("inf"),
+            json!("warm"),
+        ] {
+            assert!(check("TEMPERATURE", value.clone()).is_err(), "{}", value);
+        }
+        assert!(check("TOP_P", json!(1.1)).is_err());
+        assert!(check("TOP_P", json!(true)).is_err());
+    }
+
+    #[test]
+    fn urls_must_point_to_a_git_repository() {
+        for url in [
+            "https://github.com/danielmiessler/fabric.git",
+            "http://localhost/fabric.git",
+            "git@github.com:danielmiessler/fabric.git",
+            "ssh://git@example.com/fabric.git",
+            "file:///srv/fabric",
+        ] {
+            assert_eq!(
+                check("PATTERNS_LOADER_GIT_REPO_URL", json!(url)).as_deref(),
+                Ok(url)
+            );
+        }
+
+        for url in [
+            "",
+            "github.com/danielmiessler/fabric",
+            "ftp://example.com/fabric.git",
+            "https://example.com/my repo.git",
+            "https://example.com/\nfabric.git",
+        ] {
+            assert!(
+                check("PATTERNS_LOADER_GIT_REPO_URL", json!(url)).is_err(),
+                "{:?}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn text_must_be_a_single_line() {
+        assert_eq!(
+            check("DEFAULT_MODEL", json!(" gpt-4o ")).as_deref(),
+            Ok("gpt-4o")
+        );
+        assert!(check("DEFAULT_MODEL", json!("gpt-4o\nTEMPERATURE=2")).is_err());
+    }
+
+    #[test]
+    fn current_context_must_be_a_valid_name() {
+        assert_eq!(check("CURRENT_CONTEXT", json!("")).as_deref(), Ok(""));
+        assert_eq!(
+            check("CURRENT_CONTEXT", json!("work")).as_deref(),
+            Ok("work")
+        );
+
+        for name in ["../secrets", ".env", "a/b", "CON"] {
+            assert!(check("CURRENT_CONTEXT", json!(name)).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn custom_patterns_directory_must_be_absolute() {
+        assert_eq!(
+            check("CUSTOM_PATTERNS_DIRECTORY", json!("")).as_deref(),
+            Ok("")
+        );
+        assert!(check("CUSTOM_PATTERNS_DIRECTORY", json!("~/fabric/custom")).is_ok());
+        #[cfg(unix)]
+        assert!(check("CUSTOM_PATTERNS_DIRECTORY", json!("/srv/patterns")).is_ok());
+
+        for dir in ["custom", "./custom", "~custom"] {
+            assert!(
+                check("CUSTOM_PATTERNS_DIRECTORY", json!(dir)).is_err(),
+                "{}",
+                dir
+            );
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::fabric::secrets::load_secret;
+use crate::fabric::settings::vendors::{find_vendor, Auth, ModelsEndpoint, VendorFieldKind};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialStatus {
+    Success,
+    /// The vendor rejected the key
+    AuthError,
+    /// The vendor couldn't be reached
+    NetworkError,
+    /// The vendor answered with another error
+    Error,
+}
+
+#[derive(Serialize)]
+pub struct CredentialTest {
+    vendor: String,
+    pub status: CredentialStatus,
+    message: String,
+    /// The HTTP status, if the vendor answered
+    status_code: Option<u16>,
+    latency_ms: u128,
+}
+
+/// Requests a model-list endpoint and classifies the answer
+///
+/// ### Arguments
+///
+/// * `vendor` - The vendor name to report
+/// * `url` - The full URL of the endpoint
+/// * `endpoint` - How the endpoint authenticates
+/// * `api_key` - The key to send, if the vendor uses one
+///
+/// ### Returns
+///
+/// * `Result<CredentialTest, String>` - The outcome with its latency, or error if no HTTP client could be built
+async fn check_endpoint(
+    vendor: &str,
+    url: &str,
+    endpoint: &ModelsEndpoint,
+    api_key: Option<&str>,
+) -> Result<CredentialTest, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut request = client.get(url);
+    if let Some(api_key) = api_key {
+        request = match endpoint.auth {
+            Auth::Bearer => request.bearer_auth(api_key),
+            Auth::Header(name) => request.header(name, api_key),
+            Auth::None => request,
+        };
+    }
+    for (name, value) in endpoint.extra_headers {
+        request = request.header(*name, *value);
+    }
+
+    let started = Instant::now();
+    let response = request.send().await;
+    let latency_ms = started.elapsed().as_millis();
+
+    let (status, message, status_code) = match response {
+        Ok(response) => {
+            let code = response.status();
+            let status = if code.is_success() {
+                CredentialStatus::Success
+            } else if code == reqwest::StatusCode::UNAUTHORIZED
+                || code == reqwest::StatusCode::FORBIDDEN
+            {
+                CredentialStatus::AuthError
+            } else {
+                CredentialStatus::Error
+            };
+
+            let message = if status == CredentialStatus::Success {
+                format!("Connected to {}", url)
+            } else {
+                let body = response.text().await.unwrap_or_default();
+                format!("{}: {}", code, body.trim())
+            };
+
+            (status, message, Some(code.as_u16()))
+        }
+        Err(e) => (
+            CredentialStatus::NetworkError,
+            format!("Could not reach {}: {}", url, e),
+            None,
+        ),
+    };
+
+    Ok(CredentialTest {
+        vendor: vendor.to_string(),
+        status,
+        message,
+        status_code,
+        latency_ms,
+    })
+}
+
+/// Checks a vendor's API key by listing its models
+///
+/// The request goes to the vendor's configured base URL, or the default one
+/// when none is set.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `vendor` - The name of a vendor from the registry that has a model-list endpoint
+///
+/// ### Returns
+///
+/// * `Result<CredentialTest, String>` - The outcome with its latency, or error if the vendor is unknown or has no key
+#[tauri::command]
+pub async fn test_vendor_credentials(
+    app: tauri::AppHandle,
+    vendor: String,
+) -> Result<CredentialTest, String> {
+    let registered = find_vendor(&vendor).ok_or_else(|| format!("Unknown vendor '{}'", vendor))?;
+    let endpoint = registered
+        .models_endpoint
+        .as_ref()
+        .ok_or_else(|| format!("Checking {} credentials is not supported", registered.name))?;
+    let base_url_field = registered
+        .field(VendorFieldKind::BaseUrl)
+        .ok_or_else(|| format!("{} has no base URL", registered.name))?;
+
+    let api_key = match registered.field(VendorFieldKind::ApiKey) {
+        Some(field) => Some(
+            load_secret(app.clone(), field.key)
+                .await?
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| format!("{} is not set", field.key))?,
+        ),
+        None => None,
+    };
+
+    let base_url = load_secret(app, base_url_field.key)
+        .await?
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| base_url_field.default.to_string());
+    let url = format!("{}{}", base_url.trim().trim_end_matches('/'), endpoint.path);
+
+    check_endpoint(&vendor, &url, endpoint, api_key.as_deref()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Answers a single request with `status` and `body`, sending the request head back over the channel
+    fn mock_server(status: &'static str, body: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/models", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+                head.push(byte[0]);
+            }
+            sender
+                .send(String::from_utf8_lossy(&head).to_lowercase())
+                .unwrap();
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        (url, receiver)
+    }
+
+    fn check(url: &str, endpoint: &ModelsEndpoint, api_key: Option<&str>) -> CredentialTest {
+        tauri::async_runtime::block_on(check_endpoint("Test", url, endpoint, api_key)).unwrap()
+    }
+
+    const BEARER: ModelsEndpoint = ModelsEndpoint {
+        path: "/models",
+        auth: Auth::Bearer,
+        extra_headers: &[],
+    };
+
+    #[test]
+    fn accepted_key_is_a_success() {
+        let (url, request) = mock_server("200 OK", r#"{"data": []}"#);
+
+        let test = check(&url, &BEARER, Some("sk-test"));
+
+        assert!(test.status == CredentialStatus::Success);
+        assert_eq!(test.status_code, Some(200));
+        let request = request.recv().unwrap();
+        assert!(request.starts_with("get /v1/models "));
+        assert!(request.contains("authorization: bearer sk-test\r\n"));
+    }
+
+    #[test]
+    fn header_auth_sends_the_key_and_extra_headers() {
+        let endpoint = ModelsEndpoint {
+            path: "/models",
+            auth: Auth::Header("x-api-key"),
+            extra_headers: &[("anthropic-version", "2023-06-01")],
+        };
+        let (url, request) = mock_server("200 OK", "{}");
+
+        check(&url, &endpoint, Some("sk-ant"));
+
+        let request = request.recv().unwrap();
+        assert!(request.contains("x-api-key: sk-ant\r\n"));
+        assert!(request.contains("anthropic-version: 2023-06-01\r\n"));
+        assert!(!request.contains("authorization"));
+    }
+
+    #[test]
+    fn rejected_key_is_an_auth_error() {
+        for status in ["401 Unauthorized", "403 Forbidden"] {
+            let (url, _request) = mock_server(status, "invalid api key");
+
+            let test = check(&url, &BEARER, Some("sk-wrong"));
+
+            assert!(test.status == CredentialStatus::AuthError);
+            assert!(test.message.contains("invalid api key"));
+        }
+    }
+
+    #[test]
+    fn other_failures_are_errors() {
+        let (url, _request) = mock_server("500 Internal Server Error", "overloaded");
+
+        let test = check(&url, &BEARER, Some("sk-test"));
+
+        assert!(test.status == CredentialStatus::Error);
+        assert_eq!(test.status_code, Some(500));
+    }
+
+    #[test]
+    fn unreachable_server_is_a_network_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/models", listener.local_addr().unwrap());
+        drop(listener);
+
+        let test = check(&url, &BEARER, Some("sk-test"));
+
+        assert!(test.status == CredentialStatus::NetworkError);
+        assert_eq!(test.status_code, None);
+    }
+}
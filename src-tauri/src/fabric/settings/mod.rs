@@ -6,3 +6,12 @@ pub use model_parameters::{
     get_frequency_penalty, get_presence_penalty, get_temperature, get_top_p, set_frequency_penalty,
     set_presence_penalty, set_temperature, set_top_p,
 };
+
+pub mod vendors;
+pub use vendors::{get_vendor_registry, get_vendor_status};
+
+pub mod credentials;
+pub use credentials::test_vendor_credentials;
+
+pub mod app_settings;
+pub use app_settings::{get_settings, get_settings_schema, update_settings};
@@ -1,14 +1,20 @@
 // pub mod fabric::secrets;
 use crate::fabric::paths::get_fabric_config_dir;
+use crate::fabric::profiles::profile_env;
+use crate::fabric::secret_store::api_key_env;
+use crate::fabric::settings::vendors::find_vendor;
 use regex::Regex;
 use serde::Serialize;
 use std::process::Command;
 
 #[tauri::command]
 pub async fn refresh_models(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    // Get the output from fabric command
+    // Get the output from fabric command, it needs the API keys to ask each vendor
+    let mut env = profile_env(app_handle.clone()).await?;
+    env.extend(api_key_env(app_handle.clone()).await?);
     let output = Command::new("/usr/local/bin/fabric")
         .arg("--listmodels")
+        .envs(env)
         .output()
         .map_err(|e| format!("Failed to execute fabric command: {}", e))?;
 
@@ -46,7 +52,6 @@ pub struct FormattedModel {
 
 // TODO split into small functions
 
-// TODO make providers pulled, not provided
 #[tauri::command]
 pub async fn get_models(app_handle: tauri::AppHandle) -> Result<Vec<FormattedModel>, String> {
     // Get the config directory and create the markdown file path
@@ -73,8 +78,8 @@ pub async fn get_models(app_handle: tauri::AppHandle) -> Result<Vec<FormattedMod
         }
 
         // Check if line is a provider name
-        if ["Anthropic", "Groq", "Gemini", "OpenAI"].contains(&trimmed) {
-            current_provider = trimmed.to_string();
+        if let Some(vendor) = find_vendor(trimmed) {
+            current_provider = vendor.name.to_string();
             continue;
         }
 
@@ -109,13 +114,8 @@ pub async fn get_vendors(app_handle: tauri::AppHandle) -> Result<Vec<String>, St
     let vendors: Vec<String> = content
         .lines()
         .skip(2) // Skip the header lines
-        .filter(|line| {
-            let trimmed = line.trim();
-            !trimmed.is_empty()
-                && !trimmed.starts_with('[')
-                && ["Anthropic", "Groq", "Gemini", "OpenAI"].contains(&trimmed)
-        })
-        .map(|line| line.trim().to_string())
+        .filter_map(find_vendor)
+        .map(|vendor| vendor.name.to_string())
         .collect();
 
     Ok(vendors)
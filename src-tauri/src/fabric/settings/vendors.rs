@@ -1,28 +1,362 @@
-// pub mod fabric::secrets;
-use crate::fabric::secrets::{get_secret, update_secret};
-use std::process::Command;
+use crate::fabric::dotenv::EnvFile;
+use crate::fabric::secrets::{get_env_file_path, lookup};
+use serde::Serialize;
 
+/// Where a vendor's models run
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum VendorLocation {
+    /// A server on this machine or network, e.g. Ollama
+    Local,
+    /// A hosted API that needs an account
+    Cloud,
+}
+
+/// What a vendor's .env key holds
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VendorFieldKind {
+    ApiKey,
+    BaseUrl,
+    /// Anything else the vendor needs, e.g. Azure deployment names
+    Extra,
+}
+
+/// A .env key a vendor reads
+#[derive(Serialize)]
+pub struct VendorField {
+    pub key: &'static str,
+    pub kind: VendorFieldKind,
+    /// Whether fabric enables the vendor without it
+    pub required: bool,
+    /// The value fabric suggests during setup
+    pub default: &'static str,
+}
+
+/// How a vendor authenticates its requests
+pub enum Auth {
+    Bearer,
+    Header(&'static str),
+    None,
+}
+
+/// A vendor's endpoint for listing models, used to check its credentials
+pub struct ModelsEndpoint {
+    pub path: &'static str,
+    pub auth: Auth,
+    pub extra_headers: &'static [(&'static str, &'static str)],
+}
+
+/// A vendor fabric can send patterns to
+#[derive(Serialize)]
+pub struct Vendor {
+    /// The name as fabric prints it in `--listmodels`
+    pub name: &'static str,
+    pub location: VendorLocation,
+    pub fields: &'static [VendorField],
+    #[serde(skip)]
+    pub models_endpoint: Option<ModelsEndpoint>,
+}
+
+impl Vendor {
+    /// Gets the first field of a kind
+    pub fn field(&self, kind: VendorFieldKind) -> Option<&'static VendorField> {
+        self.fields.iter().find(|field| field.kind == kind)
+    }
+}
+
+const fn api_key(key: &'static str) -> VendorField {
+    VendorField {
+        key,
+        kind: VendorFieldKind::ApiKey,
+        required: true,
+        default: "",
+    }
+}
+
+const fn base_url(key: &'static str, default: &'static str, required: bool) -> VendorField {
+    VendorField {
+        key,
+        kind: VendorFieldKind::BaseUrl,
+        required,
+        default,
+    }
+}
+
+const fn extra(key: &'static str, required: bool) -> VendorField {
+    VendorField {
+        key,
+        kind: VendorFieldKind::Extra,
+        required,
+        default: "",
+    }
+}
+
+/// An OpenAI compatible `/models` endpoint
+const BEARER_MODELS: Option<ModelsEndpoint> = Some(ModelsEndpoint {
+    path: "/models",
+    auth: Auth::Bearer,
+    extra_headers: &[],
+});
+
+/// Every vendor fabric supports, with the .env keys its setup writes
+pub const VENDORS: &[Vendor] = &[
+    Vendor {
+        name: "OpenAI",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("OPENAI_API_KEY"),
+            base_url("OPENAI_API_BASE_URL", "https://api.openai.com/v1", false),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "Anthropic",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("ANTHROPIC_API_KEY"),
+            base_url(
+                "ANTHROPIC_API_BASE_URL",
+                "https://api.anthropic.com/v1",
+                false,
+            ),
+        ],
+        models_endpoint: Some(ModelsEndpoint {
+            path: "/models",
+            auth: Auth::Header("x-api-key"),
+            extra_headers: &[("anthropic-version", "2023-06-01")],
+        }),
+    },
+    Vendor {
+        name: "Gemini",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("GEMINI_API_KEY"),
+            base_url(
+                "GEMINI_API_BASE_URL",
+                "https://generativelanguage.googleapis.com/v1beta",
+                false,
+            ),
+        ],
+        models_endpoint: Some(ModelsEndpoint {
+            path: "/models",
+            auth: Auth::Header("x-goog-api-key"),
+            extra_headers: &[],
+        }),
+    },
+    Vendor {
+        name: "Groq",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("GROQ_API_KEY"),
+            base_url("GROQ_API_BASE_URL", "https://api.groq.com/openai/v1", false),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "Mistral",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("MISTRAL_API_KEY"),
+            base_url("MISTRAL_API_BASE_URL", "https://api.mistral.ai/v1", false),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "DeepSeek",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("DEEPSEEK_API_KEY"),
+            base_url("DEEPSEEK_API_BASE_URL", "https://api.deepseek.com", false),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "OpenRouter",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("OPENROUTER_API_KEY"),
+            base_url(
+                "OPENROUTER_API_BASE_URL",
+                "https://openrouter.ai/api/v1",
+                false,
+            ),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "SiliconCloud",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("SILICON_API_KEY"),
+            base_url(
+                "SILICON_API_BASE_URL",
+                "https://api.siliconflow.cn/v1",
+                false,
+            ),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "Cerebras",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("CEREBRAS_API_KEY"),
+            base_url("CEREBRAS_API_BASE_URL", "https://api.cerebras.ai/v1", false),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "Together",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("TOGETHER_API_KEY"),
+            base_url(
+                "TOGETHER_API_BASE_URL",
+                "https://api.together.xyz/v1",
+                false,
+            ),
+        ],
+        models_endpoint: BEARER_MODELS,
+    },
+    Vendor {
+        name: "Perplexity",
+        location: VendorLocation::Cloud,
+        fields: &[api_key("PERPLEXITY_API_KEY")],
+        models_endpoint: None,
+    },
+    Vendor {
+        name: "Azure",
+        location: VendorLocation::Cloud,
+        fields: &[
+            api_key("AZURE_API_KEY"),
+            base_url("AZURE_API_BASE_URL", "", true),
+            extra("AZURE_DEPLOYMENTS", true),
+            extra("AZURE_API_VERSION", false),
+        ],
+        models_endpoint: None,
+    },
+    Vendor {
+        name: "Ollama",
+        location: VendorLocation::Local,
+        fields: &[base_url("OLLAMA_API_URL", "http://localhost:11434", true)],
+        models_endpoint: Some(ModelsEndpoint {
+            path: "/api/tags",
+            auth: Auth::None,
+            extra_headers: &[],
+        }),
+    },
+    Vendor {
+        name: "LM Studio",
+        location: VendorLocation::Local,
+        fields: &[base_url(
+            "LM_STUDIO_API_BASE_URL",
+            "http://localhost:1234/v1",
+            true,
+        )],
+        models_endpoint: Some(ModelsEndpoint {
+            path: "/models",
+            auth: Auth::None,
+            extra_headers: &[],
+        }),
+    },
+    Vendor {
+        name: "Exolab",
+        location: VendorLocation::Local,
+        fields: &[base_url(
+            "EXOLAB_API_BASE_URL",
+            "http://localhost:52415/v1",
+            true,
+        )],
+        models_endpoint: Some(ModelsEndpoint {
+            path: "/models",
+            auth: Auth::None,
+            extra_headers: &[],
+        }),
+    },
+];
+
+/// Finds a vendor by name, ignoring case
+pub fn find_vendor(name: &str) -> Option<&'static Vendor> {
+    VENDORS
+        .iter()
+        .find(|vendor| vendor.name.eq_ignore_ascii_case(name.trim()))
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigurationStatus {
+    /// Every required key is set
+    Configured,
+    /// Some keys are set but a required one is missing
+    Partial,
+    /// None of the vendor's keys are set
+    Missing,
+}
+
+#[derive(Serialize)]
+pub struct VendorStatus {
+    vendor: &'static str,
+    location: VendorLocation,
+    status: ConfigurationStatus,
+    /// Required keys that are empty or absent
+    missing_keys: Vec<&'static str>,
+}
+
+/// Gets every vendor fabric supports with the .env keys it needs
+///
+/// ### Returns
+///
+/// * `&'static [Vendor]` - The vendors, with whether each is local or cloud
 #[tauri::command]
-pub fn get_vendors(_app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let output = Command::new("fabric")
-        .arg("--listmodels")
-        .output()
-        .map_err(|e| format!("Failed to execute fabric command: {}", e))?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+pub fn get_vendor_registry() -> &'static [Vendor] {
+    VENDORS
+}
+
+/// Reports which vendors have their required keys set in .env
+///
+/// API keys kept in the keyring count as set.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<VendorStatus>, String>` - The status of each vendor in registry order
+#[tauri::command]
+pub async fn get_vendor_status(app: tauri::AppHandle) -> Result<Vec<VendorStatus>, String> {
+    let env_path = get_env_file_path(app).await?;
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+
+    let mut statuses = Vec::new();
+    for vendor in VENDORS {
+        let mut missing_keys = Vec::new();
+        let mut any_set = false;
+        for field in vendor.fields {
+            let is_set =
+                lookup(&env, &env_path, field.key)?.is_some_and(|value| !value.trim().is_empty());
+            any_set |= is_set;
+            if field.required && !is_set {
+                missing_keys.push(field.key);
+            }
+        }
+
+        let status = if missing_keys.is_empty() {
+            ConfigurationStatus::Configured
+        } else if any_set {
+            ConfigurationStatus::Partial
+        } else {
+            ConfigurationStatus::Missing
+        };
+
+        statuses.push(VendorStatus {
+            vendor: vendor.name,
+            location: vendor.location,
+            status,
+            missing_keys,
+        });
     }
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let vendors: Vec<String> = output_str
-        .lines()
-        .filter(|line| {
-            !line.trim().starts_with('[')
-                && !line.trim().starts_with("Available models:")
-                && !line.trim().is_empty()
-        })
-        .map(|line| line.trim().to_string())
-        .collect();
-
-    Ok(vendors)
+    Ok(statuses)
 }
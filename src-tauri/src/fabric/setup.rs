@@ -0,0 +1,339 @@
+use crate::fabric::dotenv::EnvFile;
+use crate::fabric::patterns::update_patterns;
+use crate::fabric::secret_store::{api_key_backend, is_api_key};
+use crate::fabric::secrets::{get_env_file_path, lookup};
+use crate::fabric::settings::credentials::{
+    test_vendor_credentials, CredentialStatus, CredentialTest,
+};
+use crate::fabric::settings::vendors::{find_vendor, Vendor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// Where fabric's own setup points the pattern loader
+const DEFAULT_PATTERNS_REPO: (&str, &str) = (
+    "PATTERNS_LOADER_GIT_REPO_URL",
+    "https://github.com/danielmiessler/fabric.git",
+);
+const DEFAULT_PATTERNS_FOLDER: (&str, &str) =
+    ("PATTERNS_LOADER_GIT_REPO_PATTERNS_FOLDER", "patterns");
+
+/// The steps of first-time setup, in order
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    #[default]
+    Vendors,
+    Keys,
+    DefaultModel,
+    Patterns,
+    Done,
+}
+
+/// How far setup got, saved after every step so it can be resumed
+#[derive(Serialize, Deserialize, Default)]
+pub struct SetupProgress {
+    step: SetupStep,
+    /// The vendors picked in the first step
+    vendors: Vec<String>,
+    /// Vendors whose keys are set and were accepted
+    validated: Vec<String>,
+    default_vendor: Option<String>,
+    default_model: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct KeysResult {
+    /// The credential check, if the vendor has a model-list endpoint
+    test: Option<CredentialTest>,
+    progress: SetupProgress,
+}
+
+/// Gets the setup progress of the active profile, kept next to its .env file
+async fn get_progress_path(app: AppHandle) -> Result<PathBuf, String> {
+    let env_path = get_env_file_path(app).await?;
+    let profile_dir = env_path
+        .parent()
+        .ok_or_else(|| "Invalid .env file path".to_string())?;
+
+    Ok(profile_dir.join("setup.json"))
+}
+
+async fn load_progress(app: AppHandle) -> Result<SetupProgress, String> {
+    match fs::read_to_string(get_progress_path(app).await?) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse setup progress: {}", e)),
+        Err(_) => Ok(SetupProgress::default()),
+    }
+}
+
+async fn save_progress(app: AppHandle, progress: &SetupProgress) -> Result<(), String> {
+    let path = get_progress_path(app).await?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(progress)
+        .map_err(|e| format!("Failed to serialize setup progress: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to save setup progress: {}", e))
+}
+
+/// Moves on to the step after `done`, unless setup is already further along
+fn advance(progress: &mut SetupProgress, done: SetupStep, next: SetupStep) {
+    if progress.step <= done {
+        progress.step = next;
+    }
+}
+
+fn selected_vendor(progress: &SetupProgress, name: &str) -> Result<&'static Vendor, String> {
+    let vendor = find_vendor(name).ok_or_else(|| format!("Unknown vendor '{}'", name))?;
+    if !progress.vendors.iter().any(|v| v == vendor.name) {
+        return Err(format!("{} was not selected during setup", vendor.name));
+    }
+
+    Ok(vendor)
+}
+
+/// Gets how far first-time setup of the active profile got
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<SetupProgress, String>` - The current step and the choices made so far
+#[tauri::command]
+pub async fn get_setup_progress(app: AppHandle) -> Result<SetupProgress, String> {
+    load_progress(app).await
+}
+
+/// Starts setup over, leaving the .env file as it is
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<SetupProgress, String>` - The progress of a fresh setup
+#[tauri::command]
+pub async fn reset_setup(app: AppHandle) -> Result<SetupProgress, String> {
+    let progress = SetupProgress::default();
+    save_progress(app, &progress).await?;
+
+    Ok(progress)
+}
+
+/// Picks the vendors to configure
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `vendors` - Names of vendors from the registry
+///
+/// ### Returns
+///
+/// * `Result<SetupProgress, String>` - The updated progress, or error naming an unknown vendor
+#[tauri::command]
+pub async fn setup_select_vendors(
+    app: AppHandle,
+    vendors: Vec<String>,
+) -> Result<SetupProgress, String> {
+    let mut names = Vec::new();
+    for name in &vendors {
+        let vendor = find_vendor(name).ok_or_else(|| format!("Unknown vendor '{}'", name))?;
+        if !names.contains(&vendor.name.to_string()) {
+            names.push(vendor.name.to_string());
+        }
+    }
+    if names.is_empty() {
+        return Err("Select at least one vendor".to_string());
+    }
+
+    let mut progress = load_progress(app.clone()).await?;
+    progress.validated.retain(|name| names.contains(name));
+    if progress
+        .default_vendor
+        .as_ref()
+        .is_some_and(|vendor| !names.contains(vendor))
+    {
+        progress.default_vendor = None;
+        progress.default_model = None;
+    }
+    progress.vendors = names;
+
+    // A new vendor needs its keys, even if setup had moved past that step
+    progress.step = if progress.validated.len() == progress.vendors.len() {
+        progress.step.max(SetupStep::DefaultModel)
+    } else {
+        SetupStep::Keys
+    };
+
+    save_progress(app, &progress).await?;
+    Ok(progress)
+}
+
+/// Stores a vendor's keys and checks them against the vendor
+///
+/// The keys are written even if the check fails, so a network problem doesn't
+/// lose what the user typed, but only accepted keys complete the vendor.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `vendor` - A vendor picked in the first step
+/// * `values` - Values by .env key, e.g. `OPENAI_API_KEY`; empty values are skipped
+///
+/// ### Returns
+///
+/// * `Result<KeysResult, String>` - The credential check and the updated progress
+#[tauri::command]
+pub async fn setup_vendor_keys(
+    app: AppHandle,
+    vendor: String,
+    values: HashMap<String, String>,
+) -> Result<KeysResult, String> {
+    let mut progress = load_progress(app.clone()).await?;
+    let vendor = selected_vendor(&progress, &vendor)?;
+
+    let mut settings = Vec::new();
+    let mut api_keys = Vec::new();
+    for (key, value) in &values {
+        if !vendor.fields.iter().any(|field| field.key == key) {
+            return Err(format!("{} is not a {} setting", key, vendor.name));
+        }
+        let value = value.trim();
+        if value.contains(['\n', '\r']) {
+            return Err(format!("{} must be a single line", key));
+        }
+        if value.is_empty() {
+            continue;
+        }
+        if is_api_key(key) {
+            api_keys.push((key.as_str(), value));
+        } else {
+            settings.push((key.as_str(), value));
+        }
+    }
+
+    let env_path = get_env_file_path(app.clone()).await?;
+    let backend = api_key_backend(&env_path);
+    for (key, value) in &api_keys {
+        backend.set(key, value)?;
+    }
+    EnvFile::update(&env_path, |env| {
+        for (key, value) in &settings {
+            env.set(key, value);
+        }
+        Ok(())
+    })?;
+
+    let env = EnvFile::read(&env_path).unwrap_or_default();
+    for field in vendor.fields.iter().filter(|field| field.required) {
+        let is_set = lookup(&env, &env_path, field.key)?.is_some_and(|v| !v.trim().is_empty());
+        if !is_set {
+            return Err(format!("{} is required for {}", field.key, vendor.name));
+        }
+    }
+
+    let test = match vendor.models_endpoint {
+        Some(_) => Some(test_vendor_credentials(app.clone(), vendor.name.to_string()).await?),
+        None => None,
+    };
+    let accepted = test
+        .as_ref()
+        .is_none_or(|test| test.status == CredentialStatus::Success);
+
+    progress.validated.retain(|name| name != vendor.name);
+    if accepted {
+        progress.validated.push(vendor.name.to_string());
+    }
+    if progress.validated.len() == progress.vendors.len() {
+        advance(&mut progress, SetupStep::Keys, SetupStep::DefaultModel);
+    }
+
+    save_progress(app, &progress).await?;
+    Ok(KeysResult { test, progress })
+}
+
+/// Sets the vendor and model fabric uses when none is given
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `vendor` - A vendor whose keys were accepted
+/// * `model` - One of the vendor's models, e.g. from `get_models`
+///
+/// ### Returns
+///
+/// * `Result<SetupProgress, String>` - The updated progress
+#[tauri::command]
+pub async fn setup_default_model(
+    app: AppHandle,
+    vendor: String,
+    model: String,
+) -> Result<SetupProgress, String> {
+    let mut progress = load_progress(app.clone()).await?;
+    let vendor = selected_vendor(&progress, &vendor)?;
+    if !progress.validated.iter().any(|name| name == vendor.name) {
+        return Err(format!("{} is not configured yet", vendor.name));
+    }
+    let model = model.trim();
+    if model.is_empty() || model.contains(['\n', '\r']) {
+        return Err("Choose a model".to_string());
+    }
+
+    let env_path = get_env_file_path(app.clone()).await?;
+    EnvFile::update(&env_path, |env| {
+        env.set("DEFAULT_VENDOR", vendor.name);
+        env.set("DEFAULT_MODEL", model);
+        Ok(())
+    })?;
+
+    progress.default_vendor = Some(vendor.name.to_string());
+    progress.default_model = Some(model.to_string());
+    advance(&mut progress, SetupStep::DefaultModel, SetupStep::Patterns);
+
+    save_progress(app, &progress).await?;
+    Ok(progress)
+}
+
+/// Downloads the patterns, which finishes setup
+///
+/// The pattern loader settings fabric's setup writes are filled in if missing.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<SetupProgress, String>` - The finished progress, or error if the download failed
+#[tauri::command]
+pub async fn setup_download_patterns(app: AppHandle) -> Result<SetupProgress, String> {
+    let mut progress = load_progress(app.clone()).await?;
+    if progress.step < SetupStep::Patterns {
+        return Err("Choose a default model first".to_string());
+    }
+
+    let env_path = get_env_file_path(app.clone()).await?;
+    EnvFile::update(&env_path, |env| {
+        for (key, value) in [DEFAULT_PATTERNS_REPO, DEFAULT_PATTERNS_FOLDER] {
+            if env.get(key).is_none_or(|v| v.trim().is_empty()) {
+                env.set(key, value);
+            }
+        }
+        Ok(())
+    })?;
+
+    update_patterns(app.clone())
+        .await
+        .map_err(|e| format!("Failed to download patterns: {}", e))?;
+
+    progress.step = SetupStep::Done;
+    save_progress(app, &progress).await?;
+    Ok(progress)
+}
@@ -0,0 +1,572 @@
+use crate::fabric::archive::{collect_files, read_zip, write_dir, write_zip};
+use crate::fabric::diff::{diff_files, FileDiff};
+use crate::fabric::names::pattern_dir;
+use crate::fabric::paths::{get_fabric_config_dir, get_patterns_dir};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const SNAPSHOT_PREFIX: &str = "patterns-";
+
+/// How many snapshots are kept, older ones are removed when a new one is taken
+const MAX_SNAPSHOTS: usize = 20;
+
+/// Files of each pattern, keyed by pattern name and then by path inside the pattern
+type PatternFiles = BTreeMap<String, BTreeMap<String, Vec<u8>>>;
+
+#[derive(Serialize)]
+pub struct PatternSnapshot {
+    pub id: String,
+    pub created_at: u64,
+    pub size: u64,
+    pub path: PathBuf,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    /// Only present in the current patterns directory
+    Added,
+    /// Only present in the snapshot
+    Removed,
+    Modified,
+}
+
+#[derive(Serialize)]
+pub struct PatternDiff {
+    pattern: String,
+    status: DiffStatus,
+    files: Vec<FileDiff>,
+}
+
+/// Gets the directory holding the pattern snapshot archives
+async fn get_snapshots_dir(app: AppHandle) -> Result<PathBuf, String> {
+    let mut snapshots_dir = get_fabric_config_dir(app).await?;
+    snapshots_dir.push("snapshots");
+
+    Ok(snapshots_dir)
+}
+
+/// Resolves a snapshot id to its archive, making sure it exists
+async fn get_snapshot_path(app: AppHandle, snapshot: &str) -> Result<PathBuf, String> {
+    if !snapshot.starts_with(SNAPSHOT_PREFIX) || snapshot.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid snapshot id '{}'", snapshot));
+    }
+
+    let mut path = get_snapshots_dir(app).await?;
+    path.push(format!("{}.zip", snapshot));
+
+    if !path.exists() {
+        return Err(format!("Snapshot '{}' does not exist", snapshot));
+    }
+
+    Ok(path)
+}
+
+fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Gets the creation time in seconds from a snapshot id's timestamp
+///
+/// Snapshots used to be stamped in seconds, newer ones in milliseconds.
+fn stamp_to_secs(stamp: u64) -> u64 {
+    if stamp >= 100_000_000_000 {
+        stamp / 1000
+    } else {
+        stamp
+    }
+}
+
+/// Reads the patterns directory into the same shape as a snapshot archive
+fn read_patterns_dir(patterns_dir: &Path) -> Result<PatternFiles, String> {
+    let mut patterns = PatternFiles::new();
+    for (relative, path) in collect_files(patterns_dir)? {
+        let contents =
+            fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        insert_pattern_file(&mut patterns, &relative, contents);
+    }
+
+    Ok(patterns)
+}
+
+fn read_snapshot(path: &Path) -> Result<PatternFiles, String> {
+    let mut patterns = PatternFiles::new();
+    for (relative, contents) in read_zip(path)? {
+        insert_pattern_file(&mut patterns, &relative, contents);
+    }
+
+    Ok(patterns)
+}
+
+fn insert_pattern_file(patterns: &mut PatternFiles, relative: &str, contents: Vec<u8>) {
+    // Loose files at the top level (e.g. pattern_explanations.md) are kept under an empty name
+    let (pattern, file) = relative.split_once('/').unwrap_or(("", relative));
+    patterns
+        .entry(pattern.to_string())
+        .or_default()
+        .insert(file.to_string(), contents);
+}
+
+/// Archives the current patterns directory into a new timestamped snapshot
+///
+/// Only the newest [`MAX_SNAPSHOTS`] snapshots are kept.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Option<PatternSnapshot>, String>` - The new snapshot, or `None` if there were no patterns to archive
+#[tauri::command]
+pub async fn create_pattern_snapshot(app: AppHandle) -> Result<Option<PatternSnapshot>, String> {
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
+    let snapshots_dir = get_snapshots_dir(app).await?;
+
+    snapshot_patterns(&patterns_dir, &snapshots_dir)
+}
+
+/// Archives `patterns_dir` into `snapshots_dir`, see [`create_pattern_snapshot`]
+fn snapshot_patterns(
+    patterns_dir: &Path,
+    snapshots_dir: &Path,
+) -> Result<Option<PatternSnapshot>, String> {
+    let mut entries = Vec::new();
+    for (relative, path) in collect_files(patterns_dir)? {
+        let contents =
+            fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        entries.push((relative, contents));
+    }
+
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    // Two snapshots in the same millisecond get consecutive stamps
+    let mut stamp = unix_now_millis();
+    let (id, path) = loop {
+        let id = format!("{}{}", SNAPSHOT_PREFIX, stamp);
+        let path = snapshots_dir.join(format!("{}.zip", id));
+        if !path.exists() {
+            break (id, path);
+        }
+        stamp += 1;
+    };
+    let created_at = stamp_to_secs(stamp);
+
+    write_zip(&entries, &path)?;
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or_default();
+    prune_snapshots(snapshots_dir)?;
+
+    Ok(Some(PatternSnapshot {
+        id,
+        created_at,
+        size,
+        path,
+    }))
+}
+
+/// Removes the oldest snapshots beyond [`MAX_SNAPSHOTS`]
+fn prune_snapshots(snapshots_dir: &Path) -> Result<(), String> {
+    for snapshot in list_snapshots(snapshots_dir)?.iter().skip(MAX_SNAPSHOTS) {
+        if let Err(e) = fs::remove_file(&snapshot.path) {
+            println!("Failed to remove old snapshot {}: {}", snapshot.id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists all pattern snapshots, newest first
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternSnapshot>, String>` - The available snapshots or error if operation fails
+#[tauri::command]
+pub async fn list_pattern_snapshots(app: AppHandle) -> Result<Vec<PatternSnapshot>, String> {
+    list_snapshots(&get_snapshots_dir(app).await?)
+}
+
+fn list_snapshots(snapshots_dir: &Path) -> Result<Vec<PatternSnapshot>, String> {
+    if !snapshots_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(snapshots_dir)
+        .map_err(|e| format!("Failed to read snapshots directory: {}", e))?;
+
+    let mut snapshots: Vec<(u64, PatternSnapshot)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if path.extension()? != "zip" {
+                return None;
+            }
+
+            let id = path.file_stem()?.to_str()?.to_string();
+            let stamp: u64 = id.strip_prefix(SNAPSHOT_PREFIX)?.parse().ok()?;
+            let size = entry.metadata().ok()?.len();
+
+            Some((
+                stamp,
+                PatternSnapshot {
+                    id,
+                    created_at: stamp_to_secs(stamp),
+                    size,
+                    path,
+                },
+            ))
+        })
+        .collect();
+
+    // Sort by the full stamp so snapshots within the same second keep their order
+    snapshots.sort_by_key(|(stamp, _)| std::cmp::Reverse(*stamp));
+    Ok(snapshots
+        .into_iter()
+        .map(|(_, snapshot)| snapshot)
+        .collect())
+}
+
+/// Compares a snapshot with the current patterns directory
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `snapshot` - The id of the snapshot to compare against
+///
+/// ### Returns
+///
+/// * `Result<Vec<PatternDiff>, String>` - One entry per pattern that differs, with unified diffs per file
+#[tauri::command]
+pub async fn diff_pattern_snapshot(
+    app: AppHandle,
+    snapshot: String,
+) -> Result<Vec<PatternDiff>, String> {
+    let snapshot_path = get_snapshot_path(app.clone(), &snapshot).await?;
+    let patterns_dir = get_patterns_dir(app).await?;
+
+    diff_snapshot(&snapshot_path, &patterns_dir)
+}
+
+/// Compares the snapshot archive at `snapshot_path` with `patterns_dir`
+fn diff_snapshot(snapshot_path: &Path, patterns_dir: &Path) -> Result<Vec<PatternDiff>, String> {
+    let old = read_snapshot(snapshot_path)?;
+    let new = read_patterns_dir(patterns_dir)?;
+
+    let empty = BTreeMap::new();
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    let diffs = names
+        .into_iter()
+        .filter_map(|name| {
+            let status = match (old.get(name), new.get(name)) {
+                (None, Some(_)) => DiffStatus::Added,
+                (Some(_), None) => DiffStatus::Removed,
+                (Some(a), Some(b)) if a != b => DiffStatus::Modified,
+                _ => return None,
+            };
+
+            Some(PatternDiff {
+                pattern: name.clone(),
+                status,
+                files: diff_files(
+                    old.get(name).unwrap_or(&empty),
+                    new.get(name).unwrap_or(&empty),
+                    &format!("snapshot/{}", name),
+                    &format!("current/{}", name),
+                ),
+            })
+        })
+        .collect();
+
+    Ok(diffs)
+}
+
+/// Writes snapshot contents into a fresh directory
+fn write_patterns(dir: &Path, patterns: &PatternFiles) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    for (pattern, files) in patterns {
+        if pattern.is_empty() {
+            for (file, contents) in files {
+                fs::write(dir.join(file), contents)
+                    .map_err(|e| format!("Failed to write {}: {}", file, e))?;
+            }
+        } else {
+            write_dir(&dir.join(pattern), files)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Restores the whole patterns directory to the state of a snapshot
+///
+/// The current patterns are snapshotted first so the restore can be undone.
+/// The snapshot is extracted next to the patterns directory and swapped in
+/// with renames, so a failed extraction leaves the live patterns untouched.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `snapshot` - The id of the snapshot to restore
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - Success message on completion or error if operation fails
+#[tauri::command]
+pub async fn restore_pattern_snapshot(app: AppHandle, snapshot: String) -> Result<String, String> {
+    let snapshot_path = get_snapshot_path(app.clone(), &snapshot).await?;
+    let patterns_dir = get_patterns_dir(app.clone()).await?;
+    let snapshots_dir = get_snapshots_dir(app).await?;
+
+    let (restored, undo) = restore_snapshot(&snapshot_path, &patterns_dir, &snapshots_dir)?;
+
+    Ok(match undo {
+        Some(undo) => format!(
+            "Restored {} patterns from {}, the previous patterns were saved as {}",
+            restored, snapshot, undo.id
+        ),
+        None => format!("Restored {} patterns from {}", restored, snapshot),
+    })
+}
+
+/// Swaps the snapshot archive at `snapshot_path` in for `patterns_dir`, see [`restore_pattern_snapshot`]
+///
+/// ### Returns
+///
+/// * `Result<(usize, Option<PatternSnapshot>), String>` - The number of restored patterns and the snapshot of the previous patterns
+fn restore_snapshot(
+    snapshot_path: &Path,
+    patterns_dir: &Path,
+    snapshots_dir: &Path,
+) -> Result<(usize, Option<PatternSnapshot>), String> {
+    let patterns = read_snapshot(snapshot_path)?;
+
+    let parent = patterns_dir
+        .parent()
+        .ok_or_else(|| "Patterns directory has no parent".to_string())?;
+    let staging_dir = parent.join(format!(".patterns.restore-{}", std::process::id()));
+    let old_dir = parent.join(format!(".patterns.old-{}", std::process::id()));
+    for dir in [&staging_dir, &old_dir] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)
+                .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        }
+    }
+
+    if let Err(e) = write_patterns(&staging_dir, &patterns) {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(e);
+    }
+
+    let undo = snapshot_patterns(patterns_dir, snapshots_dir)?;
+
+    if patterns_dir.exists() {
+        fs::rename(patterns_dir, &old_dir)
+            .map_err(|e| format!("Failed to move the current patterns aside: {}", e))?;
+    }
+    if let Err(e) = fs::rename(&staging_dir, patterns_dir) {
+        // Put the previous patterns back
+        let _ = fs::rename(&old_dir, patterns_dir);
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(format!(
+            "Failed to move the restored patterns in place: {}",
+            e
+        ));
+    }
+
+    // The restore already happened, a leftover copy of the old patterns is only clutter
+    if old_dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&old_dir) {
+            println!(
+                "Failed to remove the previous patterns at {}: {}",
+                old_dir.display(),
+                e
+            );
+        }
+    }
+
+    // Loose top-level files aren't a pattern
+    let restored = patterns.keys().filter(|name| !name.is_empty()).count();
+    Ok((restored, undo))
+}
+
+/// Restores a single pattern from a snapshot, leaving every other pattern untouched
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle for accessing app-wide state
+/// * `snapshot` - The id of the snapshot to restore from
+/// * `pattern` - The name of the pattern to restore
+///
+/// ### Returns
+///
+/// * `Result<String, String>` - Success message on completion or error if operation fails
+#[tauri::command]
+pub async fn restore_pattern_from_snapshot(
+    app: AppHandle,
+    snapshot: String,
+    pattern: String,
+) -> Result<String, String> {
+    let snapshot_path = get_snapshot_path(app.clone(), &snapshot).await?;
+    let patterns_dir = get_patterns_dir(app).await?;
+    let pattern_dir = pattern_dir(&patterns_dir, &pattern)?;
+
+    let patterns = read_snapshot(&snapshot_path)?;
+    let files = patterns
+        .get(&pattern)
+        .ok_or_else(|| format!("Pattern '{}' is not in snapshot {}", pattern, snapshot))?;
+
+    write_dir(&pattern_dir, files)?;
+
+    Ok(format!("Restored pattern '{}' from {}", pattern, snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pattern(patterns_dir: &Path, name: &str, system: &str) {
+        let dir = patterns_dir.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("system.md"), system).unwrap();
+    }
+
+    fn read_system(patterns_dir: &Path, name: &str) -> Option<String> {
+        fs::read_to_string(patterns_dir.join(name).join("system.md")).ok()
+    }
+
+    #[test]
+    fn restoring_a_snapshot_can_be_undone() {
+        let root = tempfile::tempdir().unwrap();
+        let patterns_dir = root.path().join("patterns");
+        let snapshots_dir = root.path().join("snapshots");
+        write_pattern(&patterns_dir, "summarize", "v1");
+        fs::write(patterns_dir.join("pattern_explanations.md"), "loose").unwrap();
+        let snapshot = snapshot_patterns(&patterns_dir, &snapshots_dir)
+            .unwrap()
+            .unwrap();
+
+        write_pattern(&patterns_dir, "summarize", "v2");
+        write_pattern(&patterns_dir, "extract_wisdom", "new");
+
+        let (restored, undo) =
+            restore_snapshot(&snapshot.path, &patterns_dir, &snapshots_dir).unwrap();
+        assert_eq!(restored, 1);
+        assert_eq!(
+            read_system(&patterns_dir, "summarize").as_deref(),
+            Some("v1")
+        );
+        assert_eq!(read_system(&patterns_dir, "extract_wisdom"), None);
+        assert_eq!(
+            fs::read_to_string(patterns_dir.join("pattern_explanations.md")).unwrap(),
+            "loose"
+        );
+
+        let undo = undo.unwrap();
+        let (restored, _) = restore_snapshot(&undo.path, &patterns_dir, &snapshots_dir).unwrap();
+        assert_eq!(restored, 2);
+        assert_eq!(
+            read_system(&patterns_dir, "summarize").as_deref(),
+            Some("v2")
+        );
+        assert_eq!(
+            read_system(&patterns_dir, "extract_wisdom").as_deref(),
+            Some("new")
+        );
+
+        // Nothing is left behind next to the patterns directory
+        let mut left: Vec<String> = fs::read_dir(root.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["patterns", "snapshots"]);
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_modified_patterns() {
+        let root = tempfile::tempdir().unwrap();
+        let patterns_dir = root.path().join("patterns");
+        let snapshots_dir = root.path().join("snapshots");
+        write_pattern(&patterns_dir, "summarize", "v1");
+        write_pattern(&patterns_dir, "unchanged", "same");
+        write_pattern(&patterns_dir, "removed", "gone");
+        let snapshot = snapshot_patterns(&patterns_dir, &snapshots_dir)
+            .unwrap()
+            .unwrap();
+
+        write_pattern(&patterns_dir, "summarize", "v2");
+        write_pattern(&patterns_dir, "added", "new");
+        fs::remove_dir_all(patterns_dir.join("removed")).unwrap();
+
+        let diffs = diff_snapshot(&snapshot.path, &patterns_dir).unwrap();
+        let statuses: Vec<(&str, &DiffStatus)> = diffs
+            .iter()
+            .map(|d| (d.pattern.as_str(), &d.status))
+            .collect();
+
+        assert_eq!(statuses.len(), 3);
+        assert!(matches!(statuses[0], ("added", DiffStatus::Added)));
+        assert!(matches!(statuses[1], ("removed", DiffStatus::Removed)));
+        assert!(matches!(statuses[2], ("summarize", DiffStatus::Modified)));
+        assert_eq!(diffs[2].files.len(), 1);
+    }
+
+    #[test]
+    fn snapshots_taken_in_a_row_get_distinct_ids() {
+        let root = tempfile::tempdir().unwrap();
+        let patterns_dir = root.path().join("patterns");
+        let snapshots_dir = root.path().join("snapshots");
+        write_pattern(&patterns_dir, "summarize", "v1");
+
+        let ids: Vec<String> = (0..5)
+            .map(|_| {
+                snapshot_patterns(&patterns_dir, &snapshots_dir)
+                    .unwrap()
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
+        let listed: Vec<String> = list_snapshots(&snapshots_dir)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.id)
+            .collect();
+        assert_eq!(listed, ids.into_iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn only_the_newest_snapshots_are_kept() {
+        let root = tempfile::tempdir().unwrap();
+        let patterns_dir = root.path().join("patterns");
+        let snapshots_dir = root.path().join("snapshots");
+        write_pattern(&patterns_dir, "summarize", "v1");
+
+        let ids: Vec<String> = (0..MAX_SNAPSHOTS + 3)
+            .map(|_| {
+                snapshot_patterns(&patterns_dir, &snapshots_dir)
+                    .unwrap()
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
+        let listed = list_snapshots(&snapshots_dir).unwrap();
+        assert_eq!(listed.len(), MAX_SNAPSHOTS);
+        assert_eq!(listed[0].id, ids[ids.len() - 1]);
+        assert_eq!(listed[MAX_SNAPSHOTS - 1].id, ids[3]);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::fabric::contexts::get_contexts_dir;
+use crate::fabric::paths::{get_fabric_config_dir, get_patterns_dir};
+use crate::fabric::pattern_sources::load_pattern_sources;
+use crate::fabric::profiles::PROFILE_CHANGED;
+use crate::fabric::secrets::get_env_file_path;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Listener};
+
+/// How long the filesystem has to be quiet before changes are reported
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub const PATTERNS_CHANGED: &str = "patterns-changed";
+pub const CONTEXTS_CHANGED: &str = "contexts-changed";
+pub const SESSIONS_CHANGED: &str = "sessions-changed";
+pub const ENV_CHANGED: &str = "env-changed";
+
+/// Payload of the change events
+///
+/// `names` holds the affected patterns, contexts or sessions. It is empty when the
+/// watched directory itself changed and everything should be reloaded.
+#[derive(Serialize, Clone)]
+pub struct FsChange {
+    names: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum WatchKind {
+    Patterns,
+    Contexts,
+    Sessions,
+    Env,
+}
+
+impl WatchKind {
+    fn event(self) -> &'static str {
+        match self {
+            WatchKind::Patterns => PATTERNS_CHANGED,
+            WatchKind::Contexts => CONTEXTS_CHANGED,
+            WatchKind::Sessions => SESSIONS_CHANGED,
+            WatchKind::Env => ENV_CHANGED,
+        }
+    }
+}
+
+struct WatchRoot {
+    kind: WatchKind,
+    path: PathBuf,
+}
+
+/// Works out which watched item a changed path belongs to
+///
+/// ### Returns
+///
+/// * `Option<(WatchKind, Option<String>)>` - The kind of item and its name, `None` for the name if the root itself changed
+fn classify(roots: &[WatchRoot], path: &Path) -> Option<(WatchKind, Option<String>)> {
+    roots.iter().find_map(|root| {
+        if root.kind == WatchKind::Env {
+            return (path == root.path).then_some((WatchKind::Env, None));
+        }
+
+        let relative = path.strip_prefix(&root.path).ok()?;
+        let first = relative
+            .components()
+            .next()
+            .map(|c| Path::new(c.as_os_str()));
+
+        let name = match (root.kind, first) {
+            (_, None) => None,
+            // Pattern directories are named after the pattern
+            (WatchKind::Patterns, Some(first)) => Some(first.to_string_lossy().into_owned()),
+            // Contexts and sessions are files named after the item
+            (_, Some(first)) => Some(first.file_stem()?.to_string_lossy().into_owned()),
+        };
+
+        Some((root.kind, name))
+    })
+}
+
+/// Collects the watched directories and files
+async fn watch_roots(app: AppHandle) -> Result<Vec<WatchRoot>, String> {
+    let mut sessions_dir = get_fabric_config_dir(app.clone()).await?;
+    sessions_dir.push("sessions");
+
+    let mut roots = vec![
+        WatchRoot {
+            kind: WatchKind::Env,
+            path: get_env_file_path(app.clone()).await?,
+        },
+        WatchRoot {
+            kind: WatchKind::Patterns,
+            path: get_patterns_dir(app.clone()).await?,
+        },
+        WatchRoot {
+            kind: WatchKind::Contexts,
+            path: get_contexts_dir(app.clone())
+                .await
+                .map_err(|e| e.to_string())?,
+        },
+        WatchRoot {
+            kind: WatchKind::Sessions,
+            path: sessions_dir,
+        },
+    ];
+
+    for source in load_pattern_sources(app).await? {
+        if !roots.iter().any(|root| root.path == source.path) {
+            roots.push(WatchRoot {
+                kind: WatchKind::Patterns,
+                path: source.path,
+            });
+        }
+    }
+
+    Ok(roots)
+}
+
+/// What the watcher thread receives
+enum WatchMessage {
+    Fs(notify::Result<notify::Event>),
+    /// The active profile changed, so some watched paths did too
+    Reroot(Vec<WatchRoot>),
+}
+
+/// Roots outside the config directory, which its recursive watch doesn't cover
+fn outside_roots<'a>(
+    config_dir: &'a Path,
+    roots: &'a [WatchRoot],
+) -> impl Iterator<Item = &'a Path> + 'a {
+    roots
+        .iter()
+        .map(|root| root.path.as_path())
+        .filter(move |path| !path.starts_with(config_dir) && path.exists())
+}
+
+/// Starts watching the fabric config directory and emits debounced change events
+///
+/// The watcher runs on its own thread for the lifetime of the app. When the
+/// active profile changes, the watched paths are worked out again and every
+/// kind of change is reported so the frontend reloads.
+///
+/// ### Arguments
+///
+/// * `app` - The Tauri application handle used to emit events
+pub async fn start_watcher(app: AppHandle) -> Result<(), String> {
+    let config_dir = get_fabric_config_dir(app.clone()).await?;
+    let mut roots = watch_roots(app.clone()).await?;
+
+    let (tx, rx) = mpsc::channel();
+    let fs_tx = tx.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |event| {
+            let _ = fs_tx.send(WatchMessage::Fs(event));
+        },
+        notify::Config::default(),
+    )
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    // Watching the config directory covers patterns, contexts, sessions and .env,
+    // including directories that are only created later on
+    std::fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create {}: {}", config_dir.display(), e))?;
+    watcher
+        .watch(&config_dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", config_dir.display(), e))?;
+
+    for path in outside_roots(&config_dir, &roots) {
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+    }
+
+    let reroot_app = app.clone();
+    app.listen(PROFILE_CHANGED, move |_| {
+        let app = reroot_app.clone();
+        let tx = tx.clone();
+        tauri::async_runtime::spawn(async move {
+            match watch_roots(app).await {
+                Ok(roots) => {
+                    let _ = tx.send(WatchMessage::Reroot(roots));
+                }
+                Err(e) => println!("Failed to update watched paths: {}", e),
+            }
+        });
+    });
+
+    std::thread::spawn(move || {
+        let mut pending: BTreeMap<WatchKind, BTreeSet<String>> = BTreeMap::new();
+        let mut everything: BTreeSet<WatchKind> = BTreeSet::new();
+
+        loop {
+            // Block for the first event, then keep collecting until things settle
+            let received = if pending.is_empty() && everything.is_empty() {
+                rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected)
+            } else {
+                rx.recv_timeout(DEBOUNCE)
+            };
+
+            match received {
+                Ok(WatchMessage::Fs(Ok(event))) => {
+                    for path in &event.paths {
+                        match classify(&roots, path) {
+                            Some((kind, Some(name))) => {
+                                pending.entry(kind).or_default().insert(name);
+                            }
+                            Some((kind, None)) => {
+                                everything.insert(kind);
+                            }
+                            None => {}
+                        }
+                    }
+                }
+                Ok(WatchMessage::Fs(Err(e))) => println!("File watcher error: {}", e),
+                Ok(WatchMessage::Reroot(new_roots)) => {
+                    for path in outside_roots(&config_dir, &roots) {
+                        let _ = watcher.unwatch(path);
+                    }
+                    for path in outside_roots(&config_dir, &new_roots) {
+                        if let Err(e) = watcher.watch(path, RecursiveMode::Recursive) {
+                            println!("Failed to watch {}: {}", path.display(), e);
+                        }
+                    }
+                    roots = new_roots;
+                    everything.extend(roots.iter().map(|root| root.kind));
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    for kind in everything.iter().copied() {
+                        pending.entry(kind).or_default();
+                    }
+
+                    for (kind, names) in std::mem::take(&mut pending) {
+                        let names = if everything.contains(&kind) {
+                            Vec::new()
+                        } else {
+                            names.into_iter().collect()
+                        };
+                        if let Err(e) = app.emit(kind.event(), FsChange { names }) {
+                            println!("Failed to emit {}: {}", kind.event(), e);
+                        }
+                    }
+                    everything.clear();
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+
+    Ok(())
+}
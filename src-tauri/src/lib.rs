@@ -1,27 +1,60 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::Manager;
 
 pub mod fabric;
+use crate::fabric::config_backup::{backup_config, preview_config_restore, restore_config};
+use crate::fabric::custom_patterns::{
+    get_custom_patterns, read_custom_pattern, save_custom_pattern,
+};
 use crate::fabric::install::install_fabric;
+use crate::fabric::pattern_generator::{
+    confirm_pattern_draft, discard_pattern_draft, generate_pattern, get_pattern_drafts,
+};
+use crate::fabric::pattern_history::{
+    diff_pattern_versions, list_pattern_versions, restore_pattern_version,
+};
+use crate::fabric::pattern_lint::lint_patterns;
+use crate::fabric::pattern_packs::{export_patterns, import_patterns, preview_pattern_pack};
+use crate::fabric::pattern_selection::{
+    get_default_pattern, get_selected_pattern, set_default_pattern, set_selected_pattern,
+};
+use crate::fabric::pattern_sources::{get_pattern_sources, set_pattern_sources};
+use crate::fabric::pattern_suggestions::suggest_patterns;
+use crate::fabric::pattern_tests::{get_pattern_fixtures, save_pattern_fixtures, test_pattern};
 use crate::fabric::patterns::{
-    get_default_pattern, get_fabric_dir, get_patterns, get_patterns_git_folder,
-    get_patterns_git_repo, get_selected_pattern, set_default_pattern, set_patterns_git_folder,
-    set_patterns_git_repo, set_selected_pattern, update_patterns,
+    dry_run_pattern, get_fabric_dir, get_patterns, get_patterns_git_folder, get_patterns_git_repo,
+    preview_pattern, read_pattern, set_patterns_git_folder, set_patterns_git_repo, update_patterns,
+};
+use crate::fabric::profiles::{
+    clone_profile, create_profile, delete_profile, list_profiles, switch_profile,
 };
 use crate::fabric::run::{
     clipboard_contents_and_run_pattern, get_is_running, scrape_question_and_run_pattern,
     scrape_url_and_run_pattern, set_is_running,
 };
+use crate::fabric::secret_store::{get_secret_backend, set_secret_backend};
 use crate::fabric::secrets::{
-    get_api_keys, get_base_urls, get_env_file_path, get_secret, get_secrets, reset_secret,
-    update_secret,
+    delete_secret, get_api_keys, get_base_urls, get_env_file_path, get_secret, get_secrets,
+    reset_secret, reveal_secret, update_secret, update_secrets,
 };
+use crate::fabric::settings::app_settings::{get_settings, get_settings_schema, update_settings};
+use crate::fabric::settings::credentials::test_vendor_credentials;
 use crate::fabric::settings::model_parameters::{
     get_frequency_penalty, get_presence_penalty, get_temperature, get_top_p, set_frequency_penalty,
     set_presence_penalty, set_temperature, set_top_p,
 };
 use crate::fabric::settings::models::{get_models, get_vendors, refresh_models};
+use crate::fabric::settings::vendors::{get_vendor_registry, get_vendor_status};
+use crate::fabric::setup::{
+    get_setup_progress, reset_setup, setup_default_model, setup_download_patterns,
+    setup_select_vendors, setup_vendor_keys,
+};
+use crate::fabric::snapshots::{
+    create_pattern_snapshot, diff_pattern_snapshot, list_pattern_snapshots,
+    restore_pattern_from_snapshot, restore_pattern_snapshot,
+};
 
 pub mod plugins;
 use crate::plugins::get_clipboard_contents;
@@ -34,11 +67,13 @@ use crate::fabric::sessions::{
 };
 
 use crate::fabric::contexts::{
-    create_context_file, delete_context_file, get_contexts_dir, list_contexts, print_context,
-    read_context_file, save_context_file, set_context, set_current_context, wipe_context,
+    create_context_file, delete_context_file, duplicate_context, get_contexts_dir, list_contexts,
+    print_context, read_context_file, rename_context, save_context_file, set_context,
+    set_current_context, wipe_context,
 };
 
 use crate::fabric::paths::*;
+use crate::fabric::watcher::start_watcher;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -50,9 +85,8 @@ pub fn run() {
             app.manage(AppState {
                 fabric_folder: Mutex::new(String::new()),
                 fabric_dir: Mutex::new(PathBuf::new()),
-                default_pattern: Mutex::new(String::new()),
-                selected_pattern: Mutex::new(String::new()),
                 patterns: Mutex::new(Vec::new()),
+                pattern_drafts: Mutex::new(HashMap::new()),
                 is_running: Mutex::new(false),
                 // fabric pattern flags
                 temperature: Mutex::new(0.7),
@@ -62,6 +96,15 @@ pub fn run() {
                 model: Mutex::new(String::new()),
                 top_p: Mutex::new(0.9),
             });
+
+            // Watch ~/.config/fabric so changes made outside the app show up live
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_watcher(handle).await {
+                    println!("Failed to start file watcher: {}", e);
+                }
+            });
+
             Ok(())
         })
         .plugin(tauri_plugin_dialog::init())
@@ -87,8 +130,46 @@ pub fn run() {
             set_patterns_git_folder,
             get_default_pattern,
             set_default_pattern,
+            read_pattern,
+            preview_pattern,
+            dry_run_pattern,
+            get_pattern_sources,
+            set_pattern_sources,
+            // custom patterns
+            get_custom_patterns,
+            read_custom_pattern,
+            save_custom_pattern,
+            lint_patterns,
+            // pattern generation
+            generate_pattern,
+            get_pattern_drafts,
+            confirm_pattern_draft,
+            discard_pattern_draft,
+            // pattern suggestions
+            suggest_patterns,
+            // pattern history
+            list_pattern_versions,
+            diff_pattern_versions,
+            restore_pattern_version,
+            // pattern tests
+            get_pattern_fixtures,
+            save_pattern_fixtures,
+            test_pattern,
+            // pattern packs
+            export_patterns,
+            preview_pattern_pack,
+            import_patterns,
+            // pattern snapshots
+            create_pattern_snapshot,
+            list_pattern_snapshots,
+            diff_pattern_snapshot,
+            restore_pattern_snapshot,
+            restore_pattern_from_snapshot,
             // vendors
             get_vendors,
+            test_vendor_credentials,
+            get_vendor_registry,
+            get_vendor_status,
             // secrets
             get_env_file_path,
             get_api_keys,
@@ -97,6 +178,32 @@ pub fn run() {
             update_secret,
             get_secrets,
             reset_secret,
+            delete_secret,
+            update_secrets,
+            reveal_secret,
+            get_secret_backend,
+            set_secret_backend,
+            // setup
+            get_setup_progress,
+            reset_setup,
+            setup_select_vendors,
+            setup_vendor_keys,
+            setup_default_model,
+            setup_download_patterns,
+            // profiles
+            list_profiles,
+            create_profile,
+            clone_profile,
+            switch_profile,
+            delete_profile,
+            // settings
+            get_settings_schema,
+            get_settings,
+            update_settings,
+            // backups
+            backup_config,
+            preview_config_restore,
+            restore_config,
             // fabric LLM flags
             get_clipboard_contents,
             set_temperature,
@@ -126,11 +233,15 @@ pub fn run() {
             read_context_file,
             save_context_file,
             delete_context_file,
+            rename_context,
+            duplicate_context,
             set_current_context,
             // paths
             get_home_dir,
             get_fabric_config_dir,
             get_fabric_bin_path,
+            get_patterns_dir,
+            get_custom_patterns_dir,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
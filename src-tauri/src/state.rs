@@ -1,4 +1,5 @@
-use std::{path::PathBuf, sync::Mutex};
+use crate::fabric::pattern_generator::PatternDraft;
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
 
 pub struct AppState {
     // fabric folder
@@ -6,9 +7,8 @@ pub struct AppState {
     pub fabric_dir: Mutex<PathBuf>,
 
     // fabric pattern
-    pub default_pattern: Mutex<String>,
-    pub selected_pattern: Mutex<String>,
     pub patterns: Mutex<Vec<String>>,
+    pub pattern_drafts: Mutex<HashMap<String, PatternDraft>>,
     // fabric state
     pub is_running: Mutex<bool>,
     // fabric LLM flags